@@ -0,0 +1,87 @@
+use poise::{ChoiceParameter, serenity_prelude::{CacheHttp, Color, CreateEmbed, CreateMessage, Timestamp, UserId}};
+use tracing::warn;
+
+use crate::{format_duration, str, loadout_data::{CalamityClass, Stage}, playthrough_data::Playthrough, TimeDelta};
+
+/// Something that happened in a playthrough, posted to the playthrough's announcement channel
+pub enum PlaythroughEvent {
+    Started,
+    Joined(UserId, CalamityClass),
+    Left(UserId),
+    Kicked(UserId),
+    Progressed(Stage),
+    /// A single player moved to their own stage, from the stage they were on before
+    PlayerProgressed(UserId, Stage),
+    Ended(Option<TimeDelta>),
+    Archived,
+}
+
+impl PlaythroughEvent {
+    pub fn create_embed(&self, playthrough: &Playthrough) -> CreateEmbed {
+        let owner = playthrough.owner;
+        let (title, description, color) = match self {
+            Self::Started => (
+                str!("Playthrough Started"),
+                format!("<@{owner}>'s playthrough has started on stage `{}`. Good luck!", playthrough.stage.name()),
+                Color::FOOYOO,
+            ),
+            Self::Joined(player, class) => (
+                str!("Player Joined"),
+                format!("<@{player}> joined <@{owner}>'s playthrough as {} {}", class.name(), class.emoji()),
+                Color::FOOYOO,
+            ),
+            Self::Left(player) => (str!("Player Left"), format!("<@{player}> left <@{owner}>'s playthrough"), Color::ORANGE),
+            Self::Kicked(player) => (str!("Player Kicked"), format!("<@{player}> was kicked from <@{owner}>'s playthrough"), Color::ORANGE),
+            Self::Progressed(old_stage) => (
+                str!("Stage Progressed"),
+                format!("<@{owner}>'s playthrough progressed from `{}` to `{}`", old_stage.name(), playthrough.stage.name()),
+                Color::BLUE,
+            ),
+            Self::PlayerProgressed(player, old_stage) => {
+                let new_stage = playthrough.players.iter()
+                    .find(|p| p.user_id == *player)
+                    .map_or(playthrough.stage, |player| playthrough.stage_of(player));
+                (
+                    str!("Player Progressed"),
+                    format!("<@{player}> progressed from `{}` to `{}` in <@{owner}>'s playthrough", old_stage.name(), new_stage.name()),
+                    Color::BLUE,
+                )
+            },
+            Self::Ended(active_time) => (
+                str!("Playthrough Ended"),
+                match active_time {
+                    Some(active_time) => format!("<@{owner}>'s playthrough ended on stage `{}` after {}", playthrough.stage.name(),
+                        format_duration(*active_time)),
+                    None => format!("<@{owner}>'s playthrough ended before it started"),
+                },
+                Color::RED,
+            ),
+            Self::Archived => (
+                str!("Playthrough Archived"),
+                format!("<@{owner}>'s playthrough was archived after being inactive for too long"),
+                Color::RED,
+            ),
+        };
+
+        CreateEmbed::new()
+            .title(title)
+            .description(description)
+            .thumbnail(playthrough.stage.img())
+            .color(color)
+            .timestamp(Timestamp::now())
+    }
+}
+
+/// Posts an event to the playthrough's announcement channel, if it has one
+pub async fn announce(http: impl CacheHttp, playthrough: &Playthrough, event: PlaythroughEvent) {
+    announce_embed(http, playthrough, event.create_embed(playthrough)).await;
+}
+
+/// Posts any embed to the playthrough's announcement channel, if it has one
+pub async fn announce_embed(http: impl CacheHttp, playthrough: &Playthrough, embed: CreateEmbed) {
+    let Some(channel) = playthrough.channel else { return };
+
+    if let Err(err) = channel.send_message(http, CreateMessage::new().embed(embed)).await {
+        warn!("could not announce playthrough event in {channel}: {err}");
+    }
+}
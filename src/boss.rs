@@ -0,0 +1,140 @@
+use std::fmt::{self, Display, Formatter};
+
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use serde::{Deserialize, Serialize};
+
+use crate::loadout_data::Stage;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Hash, PartialEq, Eq, FromPrimitive)]
+pub enum Boss {
+    KingSlime,
+    DesertScourge,
+    EyeOfCthulhu,
+    Crabulon,
+    EaterOfWorldsOrBrainOfCthulhu,
+    HiveMindOrPerforators,
+    Skeletron,
+    SlimeGod,
+    WallOfFlesh,
+    TheDestroyer,
+    TheTwins,
+    SkeletronPrime,
+    CalamitasClone,
+    Plantera,
+    Golem,
+    LunaticCultist,
+    AstrumDeus,
+    MoonLord,
+    ProfanedGuardians,
+    Providence,
+    StormWeaver,
+    CeaselessVoid,
+    Signus,
+    Polterghast,
+    DevourerOfGods,
+    Yharon,
+    ExoMechs,
+    SupremeCalamitas,
+}
+
+impl Display for Boss {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl Boss {
+    pub fn all() -> impl Iterator<Item = Boss> {
+        (0_usize..).map_while(FromPrimitive::from_usize)
+    }
+
+    pub fn from_name(name: &str) -> Option<Boss> {
+        Self::all().find(|boss| boss.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub fn name(&self) -> &'static str {
+        use Boss::*;
+
+        match self {
+            KingSlime => "King Slime",
+            DesertScourge => "Desert Scourge",
+            EyeOfCthulhu => "Eye of Cthulhu",
+            Crabulon => "Crabulon",
+            EaterOfWorldsOrBrainOfCthulhu => "Eater of Worlds / Brain of Cthulhu",
+            HiveMindOrPerforators => "The Hive Mind / The Perforators",
+            Skeletron => "Skeletron",
+            SlimeGod => "The Slime God",
+            WallOfFlesh => "Wall of Flesh",
+            TheDestroyer => "The Destroyer",
+            TheTwins => "The Twins",
+            SkeletronPrime => "Skeletron Prime",
+            CalamitasClone => "Calamitas Clone",
+            Plantera => "Plantera",
+            Golem => "Golem",
+            LunaticCultist => "Lunatic Cultist",
+            AstrumDeus => "Astrum Deus",
+            MoonLord => "Moon Lord",
+            ProfanedGuardians => "Profaned Guardians",
+            Providence => "Providence, the Profaned Goddess",
+            StormWeaver => "Storm Weaver",
+            CeaselessVoid => "Ceaseless Void",
+            Signus => "Signus, Envoy of the Devourer",
+            Polterghast => "Polterghast",
+            DevourerOfGods => "The Devourer of Gods",
+            Yharon => "Yharon, Dragon of Rebirth",
+            ExoMechs => "Exo Mechs",
+            SupremeCalamitas => "Supreme Witch, Calamitas",
+        }
+    }
+
+    pub fn is_modded(&self) -> bool {
+        use Boss::*;
+
+        !matches!(self, KingSlime | EyeOfCthulhu | EaterOfWorldsOrBrainOfCthulhu | Skeletron | WallOfFlesh | TheDestroyer | TheTwins
+            | SkeletronPrime | Plantera | Golem | LunaticCultist | MoonLord)
+    }
+
+    /// The stage this boss is fought in. Defeating every boss of a stage moves a playthrough onto the next one
+    pub fn stage(&self) -> Stage {
+        use Boss::*;
+
+        match self {
+            KingSlime | DesertScourge | EyeOfCthulhu | Crabulon => Stage::PreBoss,
+            EaterOfWorldsOrBrainOfCthulhu | HiveMindOrPerforators => Stage::PreEvil,
+            Skeletron => Stage::PreSkeletron,
+            SlimeGod | WallOfFlesh => Stage::PreWall,
+            TheDestroyer | TheTwins | SkeletronPrime => Stage::PreMech,
+            CalamitasClone | Plantera => Stage::PrePlantera,
+            Golem => Stage::PreGolem,
+            LunaticCultist => Stage::PreCultist,
+            AstrumDeus | MoonLord => Stage::PreMoonLord,
+            ProfanedGuardians | Providence => Stage::PreProvidence,
+            StormWeaver | CeaselessVoid | Signus | Polterghast => Stage::PrePolterghast,
+            DevourerOfGods => Stage::PreDevourer,
+            Yharon => Stage::PreYharon,
+            ExoMechs | SupremeCalamitas => Stage::PreDraedon,
+        }
+    }
+}
+
+impl Stage {
+    pub fn bosses(&self) -> Vec<Boss> {
+        Boss::all().filter(|boss| boss.stage() == *self).collect()
+    }
+
+    /// The furthest stage that can be reached from this one given the defeated bosses. If `include_modded` is false, only vanilla
+    /// bosses gate each stage, and stages gated only by modded bosses cannot be passed
+    pub fn furthest(&self, defeated: &[Boss], include_modded: bool) -> Stage {
+        let mut stage = *self;
+        loop {
+            let bosses: Vec<Boss> = stage.bosses().into_iter().filter(|boss| include_modded || !boss.is_modded()).collect();
+            if bosses.is_empty() || !bosses.iter().all(|boss| defeated.contains(boss)) { break }
+            match FromPrimitive::from_usize(stage as usize + 1) {
+                Some(next_stage) => stage = next_stage,
+                None => break,
+            }
+        }
+        stage
+    }
+}
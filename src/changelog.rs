@@ -0,0 +1,130 @@
+use num_traits::FromPrimitive;
+use poise::ChoiceParameter;
+use reqwest::Url;
+use sqlx::{PgPool, types::chrono::{NaiveDateTime, Utc}};
+
+use crate::loadout_data::{CalamityClass, Stage};
+
+type RawChangelogEntry = (i32, Option<i32>, i16, Option<i16>, String, String, String, NaiveDateTime);
+
+/// A correction applied to the loadouts from a reported issue
+pub struct ChangelogEntry {
+    pub id: i32,
+    pub issue: Option<i32>,
+    pub stage: Stage,
+    /// The class whose loadout was changed, or none if the stage-wide data was changed
+    pub class: Option<CalamityClass>,
+    pub section: String,
+    pub before: String,
+    pub after: String,
+    pub changed_at: NaiveDateTime,
+}
+
+impl ChangelogEntry {
+    pub async fn record(
+        issue: i32,
+        class: Option<CalamityClass>,
+        stage: Stage,
+        section: &str,
+        before: &str,
+        after: &str,
+        pool: &PgPool,
+    ) {
+        sqlx::query("INSERT INTO loadout_changelog(issue_id, stage, class, section, before, after, changed_at) VALUES ($1, $2, $3, $4, $5, $6, $7)")
+            .bind(issue)
+            .bind(stage as i16)
+            .bind(class.map(|class| class as i16))
+            .bind(section)
+            .bind(before)
+            .bind(after)
+            .bind(Utc::now().naive_utc())
+            .execute(pool).await.expect("query works");
+    }
+
+    /// The `limit` most recent changes, newest first
+    pub async fn recent(limit: i64, pool: &PgPool) -> Vec<ChangelogEntry> {
+        let raw: Vec<RawChangelogEntry> = sqlx::query_as("SELECT id, issue_id, stage, class, section, before, after, changed_at
+                FROM loadout_changelog ORDER BY changed_at DESC LIMIT $1")
+            .bind(limit)
+            .fetch_all(pool).await.expect("query works");
+
+        raw.into_iter()
+            .map(|(id, issue, stage, class, section, before, after, changed_at)| ChangelogEntry {
+                id,
+                issue,
+                stage: Stage::from_i16(stage).expect("stage is valid"),
+                class: class.map(|class| CalamityClass::from_i16(class).expect("class is valid")),
+                section,
+                before,
+                after,
+                changed_at,
+            })
+            .collect()
+    }
+
+    pub fn title(&self) -> String {
+        let class = self.class.map_or("Stage-wide", |class| class.name());
+        format!("{class} - {}: {}", self.stage.name(), self.section)
+    }
+
+    /// The loadout page the change can be seen on, or the issue tracker for stage-wide changes
+    pub fn url(&self) -> Url {
+        match self.class {
+            Some(class) => crate::get_loadout_url(class, self.stage),
+            None => crate::url().join("issues/").expect("path is valid"),
+        }
+    }
+}
+
+/// Writes the changelog as an Atom feed, so servers can subscribe to loadout changes
+pub fn atom_feed(entries: &[ChangelogEntry]) -> String {
+    let feed_url = crate::url().join("issues/").expect("path is valid");
+    let updated = entries.first().map_or(Utc::now().naive_utc(), |entry| entry.changed_at);
+
+    let mut feed = format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="utf-8"?>"#,
+            r#"<feed xmlns="http://www.w3.org/2005/Atom">"#,
+            "<title>Calamity Loadout Changelog</title>",
+            "<id>{url}</id>",
+            r#"<link href="{url}"/>"#,
+            r#"<link rel="self" href="{self_url}"/>"#,
+            "<updated>{updated}</updated>",
+            "<author><name>Terraria Calamity Guide</name></author>",
+        ),
+        url = escape_xml(feed_url.as_str()),
+        self_url = escape_xml(crate::url().join("api/changelog.atom").expect("path is valid").as_str()),
+        updated = updated.and_utc().to_rfc3339(),
+    );
+    for entry in entries {
+        feed += &format!(
+            concat!(
+                "<entry>",
+                "<title>{title}</title>",
+                "<id>{feed_url}#change-{id}</id>",
+                r#"<link href="{url}"/>"#,
+                "<updated>{updated}</updated>",
+                "<content type=\"text\">Changed \"{before}\" to \"{after}\"</content>",
+                "</entry>",
+            ),
+            title = escape_xml(&entry.title()),
+            feed_url = escape_xml(feed_url.as_str()),
+            id = entry.id,
+            url = escape_xml(entry.url().as_str()),
+            updated = entry.changed_at.and_utc().to_rfc3339(),
+            before = escape_xml(&entry.before),
+            after = escape_xml(&entry.after),
+        );
+    }
+    feed += "</feed>";
+
+    feed
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -4,7 +4,10 @@ pub mod send_rules;
 pub mod loadout;
 pub mod wiki;
 pub mod playthrough;
+pub mod preferences;
 pub mod db;
 pub mod report;
+pub mod issues;
 pub mod edit_loadout;
+pub mod lfg;
 
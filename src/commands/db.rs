@@ -17,11 +17,16 @@ async fn sync(ctx: Context<'_>) -> PoiseResult {
     let pool = &ctx.data().pool;
     let loadouts = LoadoutData::load(pool);
     let playthroughs = PlaythroughData::load(pool);
-    let issues = Issues::load(ctx.http(), pool);
+    // issues are loaded while holding the lock, so names stored by a running Issues::fill_author_names are either loaded or land in
+    // the new issues
+    let issues = async {
+        let mut issues_write = data.issues.write().await;
+        *issues_write = Issues::load(pool).await;
+    };
 
     let before = Instant::now();
 
-    let (loadouts, playthroughs, issues) = tokio::join!(loadouts, playthroughs, issues);
+    let (loadouts, playthroughs, ()) = tokio::join!(loadouts, playthroughs, issues);
 
     {
         let mut loadouts_write = data.loadouts.write().await;
@@ -31,10 +36,7 @@ async fn sync(ctx: Context<'_>) -> PoiseResult {
         let mut playthroughs_write = data.playthroughs.write().await;
         *playthroughs_write = playthroughs;
     };
-    {
-        let mut issues_write = data.issues.write().await;
-        *issues_write = issues;
-    };
+    tokio::spawn(Issues::fill_author_names(ctx.serenity_context().clone(), data.issues.clone(), pool.clone()));
 
     ctx.say(format!("Successfully synced with database ({}ms)", (Instant::now() - before).as_millis())).await?;
     Ok(())
@@ -0,0 +1,496 @@
+use std::cmp::Reverse;
+
+use num_traits::FromPrimitive;
+use poise::{command, ChoiceParameter, CreateReply, serenity_prelude::{
+    self as serenity,
+    ActionRowComponent,
+    ButtonStyle,
+    ChannelId,
+    Color,
+    ComponentInteraction,
+    CreateActionRow,
+    CreateButton,
+    CreateEmbed,
+    CreateEmbedFooter,
+    CreateInputText,
+    CreateInteractionResponse,
+    CreateInteractionResponseMessage,
+    CreateMessage,
+    CreateModal,
+    GuildId,
+    InputTextStyle,
+    EditMessage,
+    Member,
+    MessageId,
+    ModalInteraction,
+    Role,
+    Timestamp,
+    User,
+    UserId,
+}};
+use tracing::warn;
+
+use crate::{
+    changelog::ChangelogEntry,
+    issue::{Issue, IssueStatus, Issues, NoIssueFound, SetStatusError},
+    loadout_data::{CalamityClass, LoadoutChange, LoadoutData, LoadoutFix, Stage},
+    str,
+    Context,
+    Data,
+    PoiseResult,
+};
+
+const PAGE_SIZE: usize = 10;
+const NOT_REVIEWER: &str = "You can't review issues. Ask an admin for this server's reviewer role";
+
+#[command(
+    slash_command,
+    subcommands("list", "view", "block", "unblock", "reviewer_role"),
+    guild_only,
+    default_member_permissions = "ADMINISTRATOR",
+    description_localized("en-US", "Browses reported loadout issues"),
+)]
+pub async fn issues(_: Context<'_>) -> PoiseResult {
+    Ok(())
+}
+
+#[command(slash_command, ephemeral, description_localized("en-US", "Lists reported issues, newest first"))]
+async fn list(
+    ctx: Context<'_>,
+    #[description = "Only show issues with this status"] status: Option<IssueStatus>,
+    #[description = "Only show issues in this class"] class: Option<CalamityClass>,
+    #[description = "Only show issues in this stage"] stage: Option<Stage>,
+    #[description = "Only show issues reported by this user"] author: Option<User>,
+) -> PoiseResult {
+    let filter = IssueFilter { status, class, stage, author: author.map(|author| author.id) };
+    let issues = ctx.data().issues.read().await;
+    let (embed, components) = create_issue_list(&issues, &filter, 1);
+    ctx.send(CreateReply::default().embed(embed).components(components)).await?;
+
+    Ok(())
+}
+
+#[command(slash_command, description_localized("en-US", "Reposts an issue so it can be reviewed"))]
+async fn view(ctx: Context<'_>, #[description = "The issue's ID, as shown in its embed"] id: String) -> PoiseResult {
+    let issue = {
+        let issues = ctx.data().issues.read().await;
+        i32::from_str_radix(id.trim().trim_start_matches("0x"), 16).ok()
+            .and_then(|id| issues.issues.get(&id))
+            .map(|issue| (issue.id, issue.create_embed(), issue.create_components()))
+    };
+    let Some((id, embed, components)) = issue else {
+        ctx.send(CreateReply::default().content(format!("There is no issue with the ID `{id}`")).ephemeral(true)).await?;
+        return Ok(());
+    };
+
+    let reply = ctx.send(CreateReply::default().embed(embed).components(components)).await?;
+    let message = reply.message().await?;
+
+    // the repost becomes the issue's review message, so the buttons on the old one are removed to keep a single copy up to date
+    let previous = ctx.data().issues.write().await.set_message(id, message.channel_id, message.id, &ctx.data().pool).await
+        .map_err(|NoIssueFound(id)| format!("issue not found: {id}"))?;
+    if let Some((channel, old_message)) = previous.filter(|(_, old_message)| *old_message != message.id) {
+        let edit = EditMessage::new().content(format!("Moved to {}", message.link())).components(Vec::new());
+        if let Err(err) = channel.edit_message(ctx, old_message, edit).await {
+            warn!("could not remove the buttons from the old message of issue {id:x}: {err}");
+        }
+    }
+
+    Ok(())
+}
+
+#[command(slash_command, ephemeral, description_localized("en-US", "Stops a user from reporting issues"))]
+async fn block(
+    ctx: Context<'_>,
+    #[description = "The user to block"] user: User,
+    #[description = "Why the user is blocked, which is shown to them"] #[max_length = 255] reason: Option<String>,
+) -> PoiseResult {
+    let mut issues = ctx.data().issues.write().await;
+    let content = match issues.block(user.id, ctx.author().id, reason, &ctx.data().pool).await {
+        true => format!("Blocked {} from reporting issues", user.name),
+        false => {
+            let blocked_by = issues.blocked.get(&user.id).expect("user is blocked").blocked_by;
+            format!("{} was already blocked from reporting issues by <@{blocked_by}>", user.name)
+        },
+    };
+    ctx.say(content).await?;
+
+    Ok(())
+}
+
+#[command(slash_command, ephemeral, description_localized("en-US", "Lets a blocked user report issues again"))]
+async fn unblock(ctx: Context<'_>, #[description = "The user to unblock"] user: User) -> PoiseResult {
+    let mut issues = ctx.data().issues.write().await;
+    let content = match issues.unblock(user.id, &ctx.data().pool).await {
+        true => format!("{} can report issues again", user.name),
+        false => format!("{} isn't blocked from reporting issues", user.name),
+    };
+    ctx.say(content).await?;
+
+    Ok(())
+}
+
+#[command(
+    slash_command,
+    ephemeral,
+    rename = "reviewer-role",
+    description_localized("en-US", "Sets the role that can review issues in this server, on top of administrators"),
+)]
+async fn reviewer_role(ctx: Context<'_>, #[description = "The reviewer role, or nothing to remove it"] role: Option<Role>) -> PoiseResult {
+    let guild = ctx.guild_id().expect("command is guild only");
+    let mut issues = ctx.data().issues.write().await;
+    issues.set_reviewer_role(guild, role.as_ref().map(|role| role.id), &ctx.data().pool).await;
+
+    let content = match role {
+        Some(role) => format!("Members with {} can now review issues", role.name),
+        None => str!("Only administrators can review issues now"),
+    };
+    ctx.say(content).await?;
+
+    Ok(())
+}
+
+/// The filters picked in `/issues list`, which are kept in the custom ids of its page buttons
+#[derive(Default)]
+struct IssueFilter {
+    status: Option<IssueStatus>,
+    class: Option<CalamityClass>,
+    stage: Option<Stage>,
+    author: Option<UserId>,
+}
+
+impl IssueFilter {
+    fn matches(&self, issue: &Issue) -> bool {
+        self.status.is_none_or(|status| issue.status == status)
+            && self.class.is_none_or(|class| issue.class == Some(class))
+            && self.stage.is_none_or(|stage| issue.stage == stage)
+            && self.author.is_none_or(|author| issue.author == author)
+    }
+
+    /// The custom id of the button that shows `page` of the issues matching this filter, which is
+    /// `issues-page-{page}-{status}-{class}-{stage}-{author}` with `x` in place of missing filters
+    fn page_custom_id(&self, page: usize) -> String {
+        fn part(value: Option<impl ToString>) -> String {
+            value.map(|value| value.to_string()).unwrap_or_else(|| String::from("x"))
+        }
+
+        format!(
+            "issues-page-{page}-{}-{}-{}-{}",
+            part(self.status.map(|status| status as u8)),
+            part(self.class.map(|class| class as u8)),
+            part(self.stage.map(|stage| stage as u8)),
+            part(self.author),
+        )
+    }
+
+    fn parse_page_custom_id(custom_id: &str) -> Option<(Self, usize)> {
+        let [page, status, class, stage, author] = custom_id.strip_prefix("issues-page-")?.split('-').collect::<Vec<_>>()[..] else {
+            return None;
+        };
+        let part = |value: &str| value.parse::<u64>().ok();
+
+        let filter = IssueFilter {
+            status: part(status).and_then(IssueStatus::from_u64),
+            class: part(class).and_then(CalamityClass::from_u64),
+            stage: part(stage).and_then(Stage::from_u64),
+            author: part(author).map(UserId::new),
+        };
+        Some((filter, page.parse().ok()?))
+    }
+}
+
+fn create_issue_list(issues: &Issues, filter: &IssueFilter, page: usize) -> (CreateEmbed, Vec<CreateActionRow>) {
+    let mut matching: Vec<&Issue> = issues.issues.values().filter(|issue| filter.matches(issue)).collect();
+    matching.sort_by_key(|issue| Reverse(issue.created_at));
+
+    let pages = matching.len().div_ceil(PAGE_SIZE).max(1);
+    let page = page.clamp(1, pages);
+    let shown = &matching[((page - 1) * PAGE_SIZE).min(matching.len())..(page * PAGE_SIZE).min(matching.len())];
+
+    let mut embed = CreateEmbed::new()
+        .title("Issues")
+        .fields(shown.iter().map(|issue| (
+            format!("{:x} - {}", issue.id, issue.status.name()),
+            format!(
+                "{} - {}\n`{}` → `{}`\nReported by <@{}> <t:{}:R>",
+                issue.class_name(),
+                issue.stage.name(),
+                issue.incorrect,
+                issue.correct,
+                issue.author,
+                issue.created_at.and_utc().timestamp(),
+            ),
+            false,
+        )))
+        .color(Color::ORANGE)
+        .footer(CreateEmbedFooter::new(format!("Page {page} of {pages} ({} issues)", matching.len())))
+        .timestamp(Timestamp::now());
+    if matching.is_empty() {
+        embed = embed.description("No issues found");
+    }
+
+    let buttons = vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(filter.page_custom_id(page - 1)).style(ButtonStyle::Secondary).label("Previous").disabled(page == 1),
+        CreateButton::new(filter.page_custom_id(page + 1)).style(ButtonStyle::Secondary).label("Next").disabled(page == pages),
+    ])];
+
+    (embed, buttons)
+}
+
+/// Handles the page buttons of `/issues list`, which have the custom id described in [`IssueFilter::page_custom_id`]
+pub async fn handle_page_interaction(ctx: &serenity::Context, interaction: &ComponentInteraction, data: &Data) -> PoiseResult {
+    let (filter, page) = IssueFilter::parse_page_custom_id(&interaction.data.custom_id).ok_or("invalid issue page custom id")?;
+    let issues = data.issues.read().await;
+    let (embed, components) = create_issue_list(&issues, &filter, page);
+
+    interaction.create_response(ctx, CreateInteractionResponse::UpdateMessage(CreateInteractionResponseMessage::new()
+        .embed(embed)
+        .components(components)
+    )).await?;
+
+    Ok(())
+}
+
+/// Handles the review buttons on issues in the issue channel, which have the custom id `issue-{action}-{id}`. Rejecting an issue or
+/// marking it as a duplicate first asks for a reason in a modal, which is handled by [`handle_modal`]. Applying a fix first shows a
+/// preview, confirmed with `issue-confirm-{id}-{issue message}`. Issues posted before statuses existed have a single resolve button
+/// with the custom id `r-{id}`
+pub async fn handle_interaction(ctx: &serenity::Context, interaction: &ComponentInteraction, data: &Data) -> PoiseResult {
+    if !is_reviewer(ctx, data, interaction.guild_id, interaction.channel_id, interaction.member.as_ref()).await {
+        interaction.create_response(ctx, CreateInteractionResponse::Message(CreateInteractionResponseMessage::new()
+            .content(NOT_REVIEWER)
+            .ephemeral(true)
+        )).await?;
+        return Ok(());
+    }
+
+    let custom_id = &interaction.data.custom_id;
+    let args: Vec<&str> = match custom_id.strip_prefix("r-") {
+        Some(id) => vec!["resolve", id],
+        None => custom_id.split('-').skip(1).collect(),
+    };
+    let [action, id, ..] = args[..] else { return Err("invalid issue custom id".into()) };
+    let id = id.parse::<i32>().map_err(|_| "issue id is a number")?;
+
+    match action {
+        "apply" => return preview_fix(ctx, interaction, data, id).await,
+        "confirm" => {
+            let message = args.get(2).and_then(|message| message.parse().ok()).map(MessageId::new).ok_or("invalid issue custom id")?;
+            return apply_fix(ctx, interaction, data, id, message).await;
+        },
+        _ => {},
+    }
+    let status = parse_status(action).ok_or("invalid issue action")?;
+
+    if let IssueStatus::Rejected | IssueStatus::Duplicate = status {
+        let (title, placeholder) = match status {
+            IssueStatus::Rejected => ("Reject Issue", "Why is this report wrong?"),
+            _ => ("Mark Issue as Duplicate", "Which issue does this duplicate?"),
+        };
+        interaction.create_response(ctx, CreateInteractionResponse::Modal(
+            CreateModal::new(format!("issue-reason-{action}-{id}"), title).components(vec![CreateActionRow::InputText(
+                CreateInputText::new(InputTextStyle::Paragraph, "Reason", "reason")
+                    .placeholder(placeholder)
+                    .max_length(255)
+                    .required(false)
+            )])
+        )).await?;
+        return Ok(());
+    }
+
+    let result = data.issues.write().await.set_status(id, status, interaction.user.id, None, &data.pool).await
+        .map(|issue| (issue.create_embed(), issue.create_components(), issue.author, issue.create_update_embed()));
+    let (embed, components, author, update) = match result {
+        Ok(update) => update,
+        Err(SetStatusError::AlreadyClosed) => {
+            interaction.create_response(ctx, already_closed_response(id)).await?;
+            return Ok(());
+        },
+        Err(SetStatusError::NoIssueFound) => return Err(format!("issue not found: {id}").into()),
+    };
+
+    interaction.create_response(ctx, CreateInteractionResponse::UpdateMessage(CreateInteractionResponseMessage::new()
+        .embed(embed)
+        .components(components)
+    )).await?;
+    notify_author(ctx, data, id, author, update).await;
+
+    Ok(())
+}
+
+/// Handles the reason modal shown when rejecting an issue or marking it as a duplicate, which has the custom id
+/// `issue-reason-{action}-{id}`
+pub async fn handle_modal(ctx: &serenity::Context, interaction: &ModalInteraction, data: &Data) -> PoiseResult {
+    if !is_reviewer(ctx, data, interaction.guild_id, interaction.channel_id, interaction.member.as_ref()).await {
+        interaction.create_response(ctx, CreateInteractionResponse::Message(CreateInteractionResponseMessage::new()
+            .content(NOT_REVIEWER)
+            .ephemeral(true)
+        )).await?;
+        return Ok(());
+    }
+
+    let [_, _, action, id] = interaction.data.custom_id.split('-').collect::<Vec<_>>()[..] else {
+        return Err("invalid issue custom id".into());
+    };
+    let id = id.parse::<i32>().map_err(|_| "issue id is a number")?;
+    let status = parse_status(action).ok_or("invalid issue action")?;
+    let reason = interaction.data.components.iter()
+        .flat_map(|row| &row.components)
+        .find_map(|component| match component {
+            ActionRowComponent::InputText(input) if input.custom_id == "reason" => input.value.clone(),
+            _ => None,
+        })
+        .filter(|reason| !reason.trim().is_empty());
+
+    let result = data.issues.write().await.set_status(id, status, interaction.user.id, reason, &data.pool).await
+        .map(|issue| (issue.create_embed(), issue.create_components(), issue.author, issue.create_update_embed()));
+    let (embed, components, author, update) = match result {
+        Ok(update) => update,
+        Err(SetStatusError::AlreadyClosed) => {
+            interaction.create_response(ctx, already_closed_response(id)).await?;
+            return Ok(());
+        },
+        Err(SetStatusError::NoIssueFound) => return Err(format!("issue not found: {id}").into()),
+    };
+
+    interaction.create_response(ctx, CreateInteractionResponse::UpdateMessage(CreateInteractionResponseMessage::new()
+        .embed(embed)
+        .components(components)
+    )).await?;
+    notify_author(ctx, data, id, author, update).await;
+
+    Ok(())
+}
+
+/// Shows the reviewer the change that the issue's correction would make to its loadout, with a button to apply it
+async fn preview_fix(ctx: &serenity::Context, interaction: &ComponentInteraction, data: &Data, id: i32) -> PoiseResult {
+    let issues = data.issues.read().await;
+    let loadouts = data.loadouts.read().await;
+
+    let response = match issues.issues.get(&id).ok_or_else(|| format!("Issue {id:x} no longer exists")).and_then(|issue| {
+        if issue.status.is_closed() { return Err(format!("Issue {id:x} is already closed")) }
+        find_fix(issue, &loadouts)
+    }) {
+        Ok(fix) => CreateInteractionResponseMessage::new()
+            .content(format!("**{}** will change from\n```\n{}\n```\nto\n```\n{}\n```", fix.section, fix.before, fix.after))
+            .components(vec![CreateActionRow::Buttons(vec![
+                CreateButton::new(format!("issue-confirm-{id}-{}", interaction.message.id)).style(ButtonStyle::Success).label("Apply and Resolve"),
+            ])]),
+        Err(error) => CreateInteractionResponseMessage::new().content(error),
+    };
+    interaction.create_response(ctx, CreateInteractionResponse::Message(response.ephemeral(true))).await?;
+
+    Ok(())
+}
+
+/// Writes the issue's correction to its loadout and resolves the issue, updating the issue's review message. Issues posted before
+/// review messages were stored update `message` in the channel the fix was previewed in instead
+async fn apply_fix(ctx: &serenity::Context, interaction: &ComponentInteraction, data: &Data, id: i32, message: MessageId) -> PoiseResult {
+    let mut issues = data.issues.write().await;
+    let mut loadouts = data.loadouts.write().await;
+    let mut resolved = None;
+
+    let fix = issues.issues.get(&id)
+        .ok_or_else(|| format!("Issue {id:x} no longer exists"))
+        .and_then(|issue| {
+            if issue.status.is_closed() { return Err(format!("Issue {id:x} is already closed")) }
+            Ok((issue.class, issue.stage, find_fix(issue, &loadouts)?))
+        });
+    let content = match fix {
+        Ok((class, stage, LoadoutFix { section, before, after, change })) => {
+            let edit_res = match (change, class) {
+                (LoadoutChange::Header(header), Some(class)) => loadouts.edit(&data.pool, stage, class, header).await.map_err(|err| err.to_string()),
+                (LoadoutChange::Extra(label, values), Some(class)) => {
+                    loadouts.set_extra(&data.pool, stage, class, label, values).await.map_err(|err| err.to_string())
+                },
+                (LoadoutChange::Potion(potion), _) => loadouts.set_potion(&data.pool, stage, potion).await.map_err(|err| err.to_string()),
+                (LoadoutChange::Powerups(powerups), _) => loadouts.set_powerups(&data.pool, stage, powerups).await.map_err(|err| err.to_string()),
+                // loadout changes are only found for issues with a class
+                (LoadoutChange::Header(_) | LoadoutChange::Extra(..), None) => Err(format!("Issue {id:x} isn't in a class's loadout")),
+            };
+            match edit_res {
+                Ok(()) => {
+                    ChangelogEntry::record(id, class, stage, &section, &before, &after, &data.pool).await;
+                    let reason = Some(format!("Applied the fix to {section}"));
+                    let issue = issues.set_status(id, IssueStatus::Resolved, interaction.user.id, reason, &data.pool).await
+                        .map_err(|_| format!("issue not found: {id}"))?;
+                    let edit = EditMessage::new().embed(issue.create_embed()).components(issue.create_components());
+                    let message = issue.message.unwrap_or((interaction.channel_id, message));
+                    resolved = Some((message, edit, issue.author, issue.create_update_embed()));
+                    format!("Applied the fix and resolved issue {id:x}")
+                },
+                Err(error) => error,
+            }
+        },
+        Err(error) => error,
+    };
+    drop(loadouts);
+    drop(issues);
+
+    if let Some(((channel, message), edit, author, update)) = resolved {
+        // the fix is already applied, so the reviewer still gets an answer if the message can't be updated
+        if let Err(err) = channel.edit_message(ctx, message, edit).await {
+            warn!("could not update the review message of issue {id:x}: {err}");
+        }
+        notify_author(ctx, data, id, author, update).await;
+    }
+    interaction.create_response(ctx, CreateInteractionResponse::UpdateMessage(CreateInteractionResponseMessage::new()
+        .content(content)
+        .components(Vec::new())
+    )).await?;
+
+    Ok(())
+}
+
+fn already_closed_response(id: i32) -> CreateInteractionResponse {
+    CreateInteractionResponse::Message(CreateInteractionResponseMessage::new()
+        .content(format!("Issue {id:x} is already closed"))
+        .ephemeral(true))
+}
+
+/// Sends the author of issue `id` its update embed, unless they turned issue updates off. Callers must not hold the issues lock, since
+/// this waits on Discord
+async fn notify_author(ctx: &serenity::Context, data: &Data, id: i32, author: UserId, update: CreateEmbed) {
+    if data.preferences.read().await.get(author).mute_issue_updates { return }
+
+    if let Err(err) = author.direct_message(ctx, CreateMessage::new().embed(update)).await {
+        warn!("could not notify {author} about issue {id:x}: {err}");
+    }
+}
+
+fn find_fix(issue: &Issue, loadouts: &LoadoutData) -> Result<LoadoutFix, String> {
+    let stage_data = loadouts.get_stage(issue.stage).ok_or_else(|| format!("There is no data for {}", issue.stage.name()))?;
+    let Some(class) = issue.class else {
+        return stage_data.find_fix(&issue.incorrect, &issue.correct).ok_or_else(|| format!(
+            "Couldn't replace `{}` with `{}` in the healing potion or powerups for {}",
+            issue.incorrect,
+            issue.correct,
+            issue.stage.name(),
+        ));
+    };
+
+    let loadout = stage_data.loadouts.get(&class)
+        .ok_or_else(|| format!("There is no {} loadout for {}", class.name(), issue.stage.name()))?;
+    loadout.find_fix(&issue.incorrect, &issue.correct)
+        .ok_or_else(|| format!("Couldn't find `{}` in the {} loadout for {}", issue.incorrect, class.name(), issue.stage.name()))
+}
+
+fn parse_status(action: &str) -> Option<IssueStatus> {
+    match action {
+        "accept" => Some(IssueStatus::Accepted),
+        "reject" => Some(IssueStatus::Rejected),
+        "duplicate" => Some(IssueStatus::Duplicate),
+        "resolve" => Some(IssueStatus::Resolved),
+        _ => None,
+    }
+}
+
+/// Members with the guild's reviewer role can review issues, along with administrators of the channel the issue is in
+async fn is_reviewer(ctx: &serenity::Context, data: &Data, guild: Option<GuildId>, channel: ChannelId, member: Option<&Member>) -> bool {
+    let (Some(guild), Some(member)) = (guild, member) else { return false };
+    if data.issues.read().await.reviewer_roles.get(&guild).is_some_and(|role| member.roles.contains(role)) { return true }
+
+    let Some(guild) = guild.to_guild_cached(ctx) else { return false };
+    let Some(channel) = guild.channels.get(&channel) else { return false };
+    guild.user_permissions_in(channel, member).administrator()
+}
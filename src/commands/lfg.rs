@@ -0,0 +1,236 @@
+use poise::{command, serenity_prelude::{
+    self as serenity,
+    ButtonStyle,
+    Color,
+    ComponentInteraction,
+    ComponentInteractionDataKind,
+    CreateActionRow,
+    CreateButton,
+    CreateEmbed,
+    CreateEmbedFooter,
+    CreateInteractionResponse,
+    CreateInteractionResponseMessage,
+    CreateMessage,
+    CreateSelectMenu,
+    CreateSelectMenuKind,
+    CreateSelectMenuOption,
+    Timestamp,
+    UserId,
+}, ChoiceParameter, CreateReply};
+
+use crate::{
+    announce::{announce, PlaythroughEvent},
+    commands::playthrough::refresh_dashboard,
+    loadout_data::{CalamityClass, Stage},
+    playthrough_data::{Difficulty, JoinPlayerError, Player, Playthrough},
+    roles,
+    str,
+    Context,
+    Data,
+    PoiseResult,
+};
+
+const PAGE_SIZE: usize = 5;
+
+#[command(slash_command, description_localized("en-US", "Browses public playthroughs that are looking for players"))]
+pub async fn lfg(
+    ctx: Context<'_>,
+    #[description = "Only show playthroughs on this stage"] stage: Option<Stage>,
+    #[description = "Only show playthroughs looking for this class"] class: Option<CalamityClass>,
+    #[description = "Only show playthroughs on this difficulty"] difficulty: Option<Difficulty>,
+    #[description = "The page of results"] #[min = 1] page: Option<usize>,
+) -> PoiseResult {
+    let playthroughs = ctx.data().playthroughs.read().await;
+    let listings: Vec<&Playthrough> = playthroughs.listings().into_iter()
+        .filter(|playthrough| stage.is_none_or(|stage| playthrough.stage == stage))
+        .filter(|playthrough| difficulty.is_none_or(|difficulty| playthrough.settings.difficulty == Some(difficulty)))
+        .filter(|playthrough| class.is_none_or(|class| accepts_class(playthrough, class)))
+        .collect();
+
+    if listings.is_empty() {
+        ctx.say("No public playthroughs found! Make yours public with `/playthrough public`").await?;
+        return Ok(());
+    }
+
+    let pages = listings.len().div_ceil(PAGE_SIZE);
+    let page = page.unwrap_or(1).clamp(1, pages);
+    let shown = &listings[(page - 1) * PAGE_SIZE..(page * PAGE_SIZE).min(listings.len())];
+
+    let fields = shown.iter().map(|playthrough| {
+        let listing = playthrough.listing.as_ref().expect("listed playthrough has a listing");
+        let wanted = playthrough.missing_classes();
+        let mut lines = vec![
+            listing.description.clone(),
+            format!("Owner: <@{}>", playthrough.owner),
+            format!("Stage: {}", playthrough.stage.name()),
+            format!("Players: {}", playthrough.players.iter().map(|player| player.class.emoji()).collect::<String>()),
+        ];
+        if let Some(difficulty) = playthrough.settings.difficulty {
+            lines.push(format!("Difficulty: {}", difficulty.name()));
+        }
+        lines.push(match wanted[..] {
+            [] if listing.wanted_classes.is_empty() => str!("Looking for: Any class"),
+            [] => str!("Looking for: No classes left"),
+            _ => {
+                let wanted = wanted.iter().map(|class| format!("{} {}", class.name(), class.emoji())).collect::<Vec<_>>();
+                format!("Looking for: {}", wanted.join(", "))
+            },
+        });
+        (format!("{}'s Playthrough", listing.owner_name), lines.join("\n"), false)
+    }).collect::<Vec<_>>();
+
+    let buttons = shown.iter().map(|playthrough| {
+        let listing = playthrough.listing.as_ref().expect("listed playthrough has a listing");
+        CreateButton::new(format!("lfg-request-{}", playthrough.owner))
+            .style(ButtonStyle::Primary)
+            .label(format!("Join {}", listing.owner_name))
+    }).collect();
+
+    ctx.send(CreateReply::default()
+        .embed(CreateEmbed::new()
+            .title("Looking for Group")
+            .url(crate::url().join("lfg/").expect("path is valid"))
+            .fields(fields)
+            .color(Color::FOOYOO)
+            .footer(CreateEmbedFooter::new(format!("Page {page} of {pages} ({} playthroughs)", listings.len())))
+            .timestamp(Timestamp::now())
+        )
+        .components(vec![CreateActionRow::Buttons(buttons)])
+    ).await?;
+
+    Ok(())
+}
+
+fn accepts_class(playthrough: &Playthrough, class: CalamityClass) -> bool {
+    let listing = playthrough.listing.as_ref().expect("listed playthrough has a listing");
+    listing.wanted_classes.is_empty() || playthrough.missing_classes().contains(&class)
+}
+
+/// Handles the buttons and select menus used to request to join a listed playthrough. Requests go through these steps, each with its
+/// own custom id:
+/// 1. `lfg-request-{owner}`: the requester picks a class
+/// 2. `lfg-class-{owner}`: the owner is sent the request
+/// 3. `lfg-accept-{owner}-{requester}-{class}` or `lfg-decline-{owner}-{requester}`: the owner answers the request
+pub async fn handle_interaction(ctx: &serenity::Context, interaction: &ComponentInteraction, data: &Data) -> PoiseResult {
+    let mut args = interaction.data.custom_id.split('-').skip(1);
+    let action = args.next().unwrap_or_default();
+    let mut ids = args.map(|id| id.parse::<u64>().ok());
+    let owner = ids.next().flatten().map(UserId::new).ok_or("invalid lfg custom id")?;
+
+    match action {
+        "request" => {
+            let playthroughs = data.playthroughs.read().await;
+            let playthrough = match playthroughs.active_playthroughs.get(&owner) {
+                _ if playthroughs.all_users.contains(&interaction.user.id) => Err("You are already in a playthrough"),
+                Some(playthrough) if playthrough.listing.is_some() => Ok(playthrough),
+                _ => Err("That playthrough is no longer looking for players"),
+            };
+            let playthrough = match playthrough {
+                Ok(playthrough) => playthrough,
+                Err(error) => return reply_ephemeral(ctx, interaction, error).await,
+            };
+
+            let options: Vec<_> = CalamityClass::all()
+                .filter(|class| playthrough.class_available(*class))
+                .map(|class| CreateSelectMenuOption::new(class.name(), (class as u8).to_string()))
+                .collect();
+            if options.is_empty() {
+                return reply_ephemeral(ctx, interaction, "Every class in that playthrough is taken").await;
+            }
+            interaction.create_response(ctx, CreateInteractionResponse::Message(CreateInteractionResponseMessage::new()
+                .content(format!("Which class do you want to play in <@{owner}>'s playthrough?"))
+                .components(vec![CreateActionRow::SelectMenu(
+                    CreateSelectMenu::new(format!("lfg-class-{owner}"), CreateSelectMenuKind::String { options }).placeholder("Class")
+                )])
+                .ephemeral(true)
+            )).await?;
+        },
+        "class" => {
+            let ComponentInteractionDataKind::StringSelect { values } = &interaction.data.kind else { return Ok(()) };
+            let class = values.first().and_then(|class| class.parse::<u8>().ok()).ok_or("invalid class")?;
+            let class_name = CalamityClass::all().nth(class as usize).ok_or("invalid class")?.name();
+
+            let requester = &interaction.user;
+            let message = CreateMessage::new()
+                .embed(CreateEmbed::new()
+                    .title("Join Request")
+                    .description(format!("{requester} wants to join your playthrough as a {class_name}"))
+                    .thumbnail(requester.avatar_url().unwrap_or_default())
+                    .color(Color::BLUE)
+                    .timestamp(Timestamp::now())
+                )
+                .components(vec![CreateActionRow::Buttons(vec![
+                    CreateButton::new(format!("lfg-accept-{owner}-{}-{class}", requester.id)).style(ButtonStyle::Success).label("Accept"),
+                    CreateButton::new(format!("lfg-decline-{owner}-{}", requester.id)).style(ButtonStyle::Danger).label("Decline"),
+                ])]);
+
+            let channel = data.playthroughs.read().await.active_playthroughs.get(&owner).and_then(|playthrough| playthrough.channel);
+            let sent = match owner.direct_message(ctx, message.clone()).await {
+                Ok(_) => true,
+                Err(_) => match channel {
+                    Some(channel) => channel.send_message(ctx, message.content(format!("<@{owner}>"))).await.is_ok(),
+                    None => false,
+                },
+            };
+
+            let content = if sent {
+                format!("Sent a request to join <@{owner}>'s playthrough! You'll be messaged once they answer")
+            } else {
+                format!("I couldn't message <@{owner}>! Ask them to add you with `/playthrough join`")
+            };
+            interaction.create_response(ctx, CreateInteractionResponse::UpdateMessage(CreateInteractionResponseMessage::new()
+                .content(content)
+                .components(Vec::new())
+            )).await?;
+        },
+        "accept" | "decline" => {
+            if interaction.user.id != owner {
+                return reply_ephemeral(ctx, interaction, "Only the owner of the playthrough can answer this request").await;
+            }
+            let requester = ids.next().flatten().map(UserId::new).ok_or("invalid lfg custom id")?;
+
+            let (content, requester_message) = if action == "accept" {
+                let class = ids.next().flatten().and_then(|class| CalamityClass::all().nth(class as usize)).ok_or("invalid class")?;
+
+                let mut playthroughs = data.playthroughs.write().await;
+                match playthroughs.join_player(&interaction.user, Player { user_id: requester, class, stage: None }, &data.pool).await {
+                    Ok(()) => {
+                        let playthrough = playthroughs.active_playthroughs.get(&owner).expect("joined playthrough exists");
+                        announce(ctx, playthrough, PlaythroughEvent::Joined(requester, class)).await;
+                        roles::add_player(ctx, playthrough, requester).await;
+                        refresh_dashboard(ctx, playthrough).await;
+                        (format!("Accepted <@{requester}>'s request"), format!("<@{owner}> accepted your request to join their playthrough!"))
+                    },
+                    Err(JoinPlayerError::AlreadyInPlaythrough) => {
+                        return reply_ephemeral(ctx, interaction, "That player is already in a playthrough").await;
+                    },
+                    Err(JoinPlayerError::ClassTaken) => {
+                        return reply_ephemeral(ctx, interaction, &format!("Someone is already playing {}", class.name())).await;
+                    },
+                    Err(JoinPlayerError::PlayerNotInPlaythrough | JoinPlayerError::PlayerNotOwner) => {
+                        return reply_ephemeral(ctx, interaction, "You are no longer the owner of a playthrough").await;
+                    },
+                }
+            } else {
+                (format!("Declined <@{requester}>'s request"), format!("<@{owner}> declined your request to join their playthrough"))
+            };
+
+            interaction.create_response(ctx, CreateInteractionResponse::UpdateMessage(CreateInteractionResponseMessage::new()
+                .content(content)
+                .components(Vec::new())
+            )).await?;
+            let _ = requester.direct_message(ctx, CreateMessage::new().content(requester_message)).await;
+        },
+        _ => {},
+    }
+
+    Ok(())
+}
+
+async fn reply_ephemeral(ctx: &serenity::Context, interaction: &ComponentInteraction, content: &str) -> PoiseResult {
+    interaction.create_response(ctx, CreateInteractionResponse::Message(CreateInteractionResponseMessage::new()
+        .content(content)
+        .ephemeral(true)
+    )).await?;
+    Ok(())
+}
@@ -1,8 +1,8 @@
-use poise::{command, CreateReply};
+use poise::{command, serenity_prelude::{Attachment, Color, CreateEmbed, CreateEmbedFooter, Timestamp}, ChoiceParameter, CreateReply};
 
-use crate::{Context, PoiseResult, loadout_data::{CalamityClass, Stage}};
+use crate::{Context, PoiseResult, commands::wiki, loadout_data::{CalamityClass, Stage}, player_file};
 
-#[command(slash_command, subcommands("view"), description_localized("en-US", "Loadout commands"))]
+#[command(slash_command, subcommands("view", "check"), description_localized("en-US", "Loadout commands"))]
 pub async fn loadout(_: Context<'_>) -> PoiseResult {
     Ok(())
 }
@@ -23,3 +23,82 @@ async fn view(
     Ok(())
 }
 
+#[command(slash_command, description_localized("en-US", "Checks which items of a loadout you already have"))]
+async fn check(
+    ctx: Context<'_>,
+    #[description = "Your player file (.plr or .tplr)"] file: Option<Attachment>,
+    #[description = "Your other player file, to check both vanilla (.plr) and modded (.tplr) items"] other_file: Option<Attachment>,
+    #[description = "A comma separated list of items you have"] items: Option<String>,
+    #[description = "The class. Defaults to your class in your playthrough"] class: Option<CalamityClass>,
+    #[description = "The stage of the game. Defaults to your stage in your playthrough"] stage: Option<Stage>,
+) -> PoiseResult {
+    ctx.defer().await?;
+
+    let mut owned_items: Vec<String> = items.iter()
+        .flat_map(|items| items.split([',', '\n']))
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect();
+    for file in file.iter().chain(&other_file) {
+        match player_file::parse_items(&file.download().await?) {
+            Ok(items) => {
+                owned_items.extend(items.names);
+                owned_items.extend(wiki::vanilla_item_names(&items.vanilla_ids).await?);
+            },
+            Err(err) => {
+                ctx.say(format!("Could not read your player file: {err}")).await?;
+                return Ok(());
+            },
+        }
+    }
+    if owned_items.is_empty() {
+        ctx.say("Please upload your player file or provide a list of items").await?;
+        return Ok(());
+    }
+
+    let (class, stage) = {
+        let playthroughs = ctx.data().playthroughs.read().await;
+        let playthrough = playthroughs.get_playthrough(ctx.author().id);
+        // players can be on an earlier stage than the playthrough, so their own stage is used
+        let player = playthrough.and_then(|playthrough| {
+            playthrough.players.iter().find(|player| player.user_id == ctx.author().id).map(|player| (playthrough, player))
+        });
+        (class.or(player.map(|(_, player)| player.class)), stage.or(player.map(|(playthrough, player)| playthrough.stage_of(player))))
+    };
+    let (Some(class), Some(stage)) = (class, stage) else {
+        ctx.say("You are not in a playthrough, so please specify a class and stage").await?;
+        return Ok(());
+    };
+
+    let loadout_data = ctx.data().loadouts.read().await;
+    let Some(loadout) = loadout_data.get_loadout(stage, class) else {
+        ctx.say("No loadout found! Please report this!").await?;
+        return Ok(());
+    };
+
+    let checks = loadout.check(&owned_items);
+    let (owned, total) = checks.iter()
+        .flat_map(|check| &check.entries)
+        .fold((0, 0), |(owned, total), (_, has)| (owned + *has as usize, total + 1));
+
+    ctx.send(CreateReply::default()
+        .embed(CreateEmbed::new()
+            .url(crate::get_loadout_url(class, stage))
+            .title(format!("{} - {}", class.name(), stage.name()))
+            .description(format!("You have {owned} out of {total} recommended items"))
+            .thumbnail(stage.img())
+            .fields(checks.iter().map(|check| {
+                let entries = check.entries.iter()
+                    .map(|(entry, has)| format!("{} {entry}", if *has { ":white_check_mark:" } else { ":x:" }))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                (check.section, entries, true)
+            }))
+            .color(Color::DARK_RED)
+            .footer(CreateEmbedFooter::new("Loadouts by GitGudWO").icon_url(crate::get_asset("gitgudpfp.jpg")))
+            .timestamp(Timestamp::now())
+        )
+    ).await?;
+
+    Ok(())
+}
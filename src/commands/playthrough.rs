@@ -1,14 +1,19 @@
-use std::{vec, result::Result as StdResult, convert::Into};
+use std::{vec, iter, result::Result as StdResult, convert::Into, time::Duration};
 
 use futures::future::join_all;
-use poise::{command, serenity_prelude::{User, Color, Timestamp, CacheHttp, CreateEmbed, CreateMessage, CreateEmbedFooter}, ChoiceParameter, CreateReply};
+use num_traits::FromPrimitive;
+use poise::{command, serenity_prelude::{self as serenity, ComponentInteraction, ComponentInteractionDataKind, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption, EditInteractionResponse, EditMessage, UserId, User, Color, Timestamp, CacheHttp, CreateEmbed, CreateMessage, CreateEmbedFooter, Attachment, ButtonStyle, CreateActionRow, CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage, GuildChannel, ChannelType, CreateThread, ChannelId, Http}, ChoiceParameter, CreateReply};
 use sqlx::types::chrono::Utc;
+use tracing::warn;
 
-use crate::{bulleted, loadout_data::{CalamityClass, LoadoutData, Stage}, ordered, playthrough_data::{FinishPlaythroughError, InPlaythroughError, JoinPlayerError, KickError, LeaveError, Player, Playthrough, ProgressError, StartPlaythroughError}, str, Context, PoiseResult};
+use crate::{announce::{announce, announce_embed, PlaythroughEvent}, recap::Recap, boss::Boss, bulleted, format_duration, parse_duration, preferences::{Delivery, PreferenceData}, roles, loadout_data::{self, CalamityClass, LoadoutData, Stage}, ordered, playthrough_data::{DefeatBossError, FinishPlaythroughError, InPlaythroughError, JoinPlayerError, KickError, LeaveError, PauseError, Player, Playthrough, ProgressError, RemindError, ResumeError, SetChannelError, SetRoleError, StartPlaythroughError, UpdateSettingsError, SetListingError, Listing, Difficulty, WorldSize, WorldEvil, PlaythroughSettings, PlaythroughData, Setting, StartVoteError, CastVoteError, ChangeClassError}, str, vote::{self, ProgressVote, VoteOutcome}, world_file::{self, WorldFile}, Context, Data, PoiseResult, TimeDelta};
+
+/// How far ahead reminders can be set
+const MAX_REMINDER_DAYS: i64 = 365;
 
 #[command(
     slash_command,
-    subcommands("list", "view", "create", "end", "start", "join", "kick", "leave", "progress"),
+    subcommands("list", "view", "dashboard", "create", "end", "start", "join", "kick", "leave", "progress", "pause", "resume", "remind", "channel", "role", "settings", "public", "private", "suggest", "defeated", "checklist", "sync_world"),
     description_localized("en-US", "All playthrough related commands"),
 )]
 pub async fn playthrough(_: Context<'_>) -> PoiseResult {
@@ -47,25 +52,262 @@ async fn view(
                 .find_map(|(_, playthrough)| playthrough.players.iter().find(|player| player.user_id == user.id).and(Some(playthrough)))
         }).expect("found playthrough player is in");
 
-    let owner = playthrough.owner.to_user(ctx).await.expect("owner is a user");
+    ctx.send(CreateReply::default().embed(playthrough_embed(ctx, playthrough).await)).await?;
+
+    Ok(())
+}
+
+/// The embed showing an overview of a playthrough, used by `view` and the dashboard
+async fn playthrough_embed(http: impl CacheHttp, playthrough: &Playthrough) -> CreateEmbed {
+    let http = http.http();
+    let owner = playthrough.owner.to_user(http).await.expect("owner is a user");
     let player_list = join_all(playthrough.players.iter()
-        .map(|p| async move { format!("{} - {}{}", p.user_id.to_user(ctx).await.expect("player is user").name, p.class.name(), p.class.emoji()) })).await;
+        .map(|p| async move {
+            let name = p.user_id.to_user(http).await.expect("player is user").name;
+            match p.stage {
+                Some(stage) => format!("{name} - {}{} (on `{}`)", p.class.name(), p.class.emoji(), stage.name()),
+                None => format!("{name} - {}{}", p.class.name(), p.class.emoji()),
+            }
+        })).await;
 
-    ctx.send(CreateReply::default()
-        .embed(CreateEmbed::new()
-            .title(format!("{}'s Playthrough", owner.name))
-            .thumbnail(owner.avatar_url().unwrap_or_default())
-            .field("Players", bulleted(&player_list).to_string(), false)
-            .field("Date Started", match playthrough.started {
-                Some(date) => format!("<t:{}:D>", date.and_utc().timestamp()),
-                None => str!("Playthrough hasn't started yet"),
-            }, true)
-            .field("Game Stage", playthrough.stage.name(), true)
-            .color(Color::FOOYOO)
-            .footer(CreateEmbedFooter::new("Loadouts by GitGudWO").icon_url(crate::get_asset("gitgudpfp.jpg")))
-            .timestamp(Timestamp::now())
-        )
+    CreateEmbed::new()
+        .title(format!("{}'s Playthrough", owner.name))
+        .thumbnail(owner.avatar_url().unwrap_or_default())
+        .field("Players", bulleted(&player_list).to_string(), false)
+        .field("Date Started", match playthrough.started {
+            Some(date) => format!("<t:{}:D>", date.and_utc().timestamp()),
+            None => str!("Playthrough hasn't started yet"),
+        }, true)
+        .field("Active Time", match playthrough.active_time(Utc::now().naive_utc()) {
+            Some(active_time) if playthrough.is_paused() => format!("{} (Paused)", format_duration(active_time)),
+            Some(active_time) => format_duration(active_time),
+            None => str!("Playthrough hasn't started yet"),
+        }, true)
+        .field("Game Stage", playthrough.stage.name(), true)
+        .field("Class Coverage", class_coverage(playthrough), true)
+        .fields(settings_fields(&playthrough.settings))
+        .color(Color::FOOYOO)
+        .footer(CreateEmbedFooter::new("Loadouts by GitGudWO").icon_url(crate::get_asset("gitgudpfp.jpg")))
+        .timestamp(Timestamp::now())
+}
+
+#[command(slash_command, description_localized("en-US", "Posts a dashboard with buttons to manage the playthrough you're in"))]
+async fn dashboard(ctx: Context<'_>) -> PoiseResult {
+    ctx.defer().await?;
+
+    let mut playthroughs = ctx.data().playthroughs.write().await;
+    let Some(playthrough) = playthroughs.get_playthrough(ctx.author().id) else {
+        ctx.say("You are not in a playthrough").await?;
+        return Ok(());
+    };
+
+    let reply = ctx.send(CreateReply::default()
+        .embed(playthrough_embed(ctx, playthrough).await)
+        .components(dashboard_components(playthrough))
     ).await?;
+    let message = reply.message().await?;
+
+    // only the newest dashboard is kept up to date, so the old one's buttons are removed
+    if let Ok(Some((channel, old_message))) = playthroughs.set_dashboard(ctx.author(), Some((message.channel_id, message.id)), &ctx.data().pool).await {
+        let edit = EditMessage::new().content("This dashboard has moved to a newer message").components(Vec::new());
+        let _ = channel.edit_message(ctx, old_message, edit).await;
+    }
+
+    Ok(())
+}
+
+fn dashboard_components(playthrough: &Playthrough) -> Vec<CreateActionRow> {
+    let owner = playthrough.owner;
+    let pause = if playthrough.is_paused() {
+        CreateButton::new(format!("dash-resume-{owner}")).style(ButtonStyle::Primary).label("Resume")
+    } else {
+        CreateButton::new(format!("dash-pause-{owner}")).style(ButtonStyle::Secondary).label("Pause").disabled(playthrough.started.is_none())
+    };
+    let classes = CalamityClass::all()
+        .map(|class| CreateSelectMenuOption::new(class.name(), (class as u8).to_string()))
+        .collect();
+
+    vec![
+        CreateActionRow::Buttons(vec![
+            CreateButton::new(format!("dash-start-{owner}")).style(ButtonStyle::Success).label("Start").disabled(playthrough.started.is_some()),
+            CreateButton::new(format!("dash-progress-{owner}")).style(ButtonStyle::Primary).label("Progress"),
+            pause,
+            CreateButton::new(format!("dash-leave-{owner}")).style(ButtonStyle::Secondary).label("Leave"),
+            CreateButton::new(format!("dash-end-{owner}")).style(ButtonStyle::Danger).label("End"),
+        ]),
+        CreateActionRow::SelectMenu(
+            CreateSelectMenu::new(format!("dash-class-{owner}"), CreateSelectMenuKind::String { options: classes }).placeholder("Change your class")
+        ),
+    ]
+}
+
+/// Updates the playthrough's dashboard message, if it has one, to show its current state
+pub async fn refresh_dashboard(http: impl CacheHttp, playthrough: &Playthrough) {
+    let Some((channel, message)) = playthrough.dashboard else { return };
+
+    let edit = EditMessage::new().embed(playthrough_embed(&http, playthrough).await).components(dashboard_components(playthrough));
+    if let Err(err) = channel.edit_message(http, message, edit).await {
+        warn!("could not refresh playthrough dashboard in {channel}: {err}");
+    }
+}
+
+/// Removes the buttons from the dashboard of a playthrough that is over
+pub async fn close_dashboard(http: impl CacheHttp, playthrough: &Playthrough) {
+    let Some((channel, message)) = playthrough.dashboard else { return };
+
+    let edit = EditMessage::new().content("This playthrough is over").components(Vec::new());
+    if let Err(err) = channel.edit_message(http, message, edit).await {
+        warn!("could not close playthrough dashboard in {channel}: {err}");
+    }
+}
+
+/// Handles the dashboard's buttons and class select menu, which have the custom id `dash-{action}-{owner}`
+pub async fn handle_dashboard_interaction(ctx: &serenity::Context, interaction: &ComponentInteraction, data: &Data) -> PoiseResult {
+    let mut args = interaction.data.custom_id.split('-').skip(1);
+    let action = args.next().unwrap_or_default();
+    let owner = args.next().and_then(|id| id.parse().ok()).map(UserId::new).ok_or("invalid dashboard custom id")?;
+    let user = &interaction.user;
+    let pool = &data.pool;
+
+    interaction.create_response(ctx, CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new().ephemeral(true))).await?;
+
+    let mut playthroughs = data.playthroughs.write().await;
+    let loadouts = data.loadouts.read().await;
+    let preferences = data.preferences.read().await;
+
+    let content = if playthroughs.get_playthrough(user.id).is_none_or(|playthrough| playthrough.owner != owner) {
+        str!("You are not in this playthrough")
+    } else {
+        match action {
+            "start" => match playthroughs.start(user, pool).await {
+                Ok(()) => {
+                    let playthrough = playthroughs.active_playthroughs.get(&owner).expect("started playthrough exists");
+                    announce(ctx, playthrough, PlaythroughEvent::Started).await;
+                    resend_loadouts(ctx, playthrough, &loadouts, &preferences).await;
+                    str!("Started your playthrough!")
+                },
+                Err(StartPlaythroughError::NotOwner) => str!("Only the owner can start the playthrough"),
+                Err(StartPlaythroughError::AlreadyStarted) => str!("This playthrough has already started"),
+                Err(StartPlaythroughError::NotInPlaythrough) => str!("You are not in a playthrough"),
+            },
+            // like `/playthrough progress`, players other than the owner open a vote when voting is on
+            "progress" if playthroughs.get_playthrough(user.id).is_some_and(|playthrough| playthrough.owner != user.id && playthrough.settings.vote_majority.is_some()) => {
+                let playthrough = playthroughs.get_playthrough(user.id).expect("user is in a playthrough");
+                match new_vote_stage(playthrough, None) {
+                    Ok(new_stage) => {
+                        let opening = interaction.channel_id.say(ctx, format!("Opening a vote to progress to `{}`...", new_stage.name())).await;
+                        match opening {
+                            Ok(message) => {
+                                let vote = ProgressVote {
+                                    stage: new_stage,
+                                    started_by: user.id,
+                                    channel: interaction.channel_id,
+                                    message: message.id,
+                                    expires_at: Utc::now().naive_utc() + vote::vote_duration(),
+                                    ballots: Vec::new(),
+                                };
+                                let (edit, content) = match playthroughs.start_vote(user, vote, pool).await {
+                                    Ok(outcome) => {
+                                        let (embed, components) = conclude_vote(ctx, &mut playthroughs, owner, outcome, &loadouts, &preferences, pool).await;
+                                        (EditMessage::new().content("").embed(embed).components(components), format!("Opened a vote to progress to `{}`", new_stage.name()))
+                                    },
+                                    Err(err) => {
+                                        let error = start_vote_error(err);
+                                        (EditMessage::new().content(error), str!(error))
+                                    },
+                                };
+                                if let Err(err) = interaction.channel_id.edit_message(ctx, message.id, edit).await {
+                                    warn!("could not update vote in {}: {err}", interaction.channel_id);
+                                }
+                                content
+                            },
+                            Err(_) => str!("I can't send messages in this channel, so I can't open a vote here"),
+                        }
+                    },
+                    Err(message) => str!(message),
+                }
+            },
+            "progress" => {
+                let old_stage = playthroughs.get_playthrough(user.id).map(|playthrough| playthrough.stage).unwrap_or_default();
+                match playthroughs.progress(user.id, None, pool).await {
+                    Ok(playthrough) => {
+                        announce(ctx, playthrough, PlaythroughEvent::Progressed(old_stage)).await;
+                        if playthrough.started.is_some() {
+                            resend_loadouts(ctx, playthrough, &loadouts, &preferences).await;
+                        }
+                        format!("Progressed to stage `{}`", playthrough.stage.name())
+                    },
+                    Err(ProgressError::NotOwner) => {
+                        str!("Only the owner can progress the whole playthrough. Use `/playthrough progress` to vote or to progress on your own")
+                    },
+                    Err(ProgressError::NotInPlaythrough) => str!("You are not in a playthrough"),
+                    Err(ProgressError::LastStage) => str!("You are already on the last stage of the game"),
+                }
+            },
+            "pause" => match playthroughs.pause(user, pool).await {
+                Ok(()) => str!("Paused your playthrough"),
+                Err(PauseError::NotOwner) => str!("Only the owner can pause the playthrough"),
+                Err(PauseError::NotInPlaythrough) => str!("You are not in a playthrough"),
+                Err(PauseError::NotStarted) => str!("This playthrough hasn't started yet"),
+                Err(PauseError::AlreadyPaused) => str!("This playthrough is already paused"),
+            },
+            "resume" => match playthroughs.resume(user, pool).await {
+                Ok(paused_for) => format!("Resumed your playthrough after {}", format_duration(paused_for)),
+                Err(ResumeError::NotOwner) => str!("Only the owner can resume the playthrough"),
+                Err(ResumeError::NotInPlaythrough) => str!("You are not in a playthrough"),
+                Err(ResumeError::AlreadyRunning) => str!("This playthrough is not paused"),
+            },
+            "leave" => match playthroughs.leave(user, pool).await {
+                Ok(playthrough) => {
+                    announce(ctx, playthrough, PlaythroughEvent::Left(user.id)).await;
+                    roles::remove_player(ctx, playthrough, user.id).await;
+                    format!("Successfully left <@{owner}>'s playthrough")
+                },
+                Err(LeaveError::NotInPlaythrough) => str!("You are not in a playthrough"),
+                Err(LeaveError::OwnerOfPlaythrough) => str!("You cannot leave the playthrough you are an owner of. End it instead"),
+            },
+            "end" => match playthroughs.end(user, pool).await {
+                Ok(playthrough) => {
+                    let _ = finish_playthrough(ctx, &playthrough, false, pool).await;
+                    str!("Successfully ended your playthrough")
+                },
+                Err(FinishPlaythroughError::NotOwner) => str!("Only the owner can end the playthrough"),
+                Err(FinishPlaythroughError::NotInPlaythrough) => str!("You are not in a playthrough"),
+            },
+            "class" => {
+                let class = match &interaction.data.kind {
+                    ComponentInteractionDataKind::StringSelect { values } => values.first()
+                        .and_then(|class| class.parse::<u8>().ok())
+                        .and_then(|class| CalamityClass::all().nth(class as usize)),
+                    _ => None,
+                };
+                match class {
+                    Some(class) => match playthroughs.change_class(user, class, pool).await {
+                        Ok(playthrough) => {
+                            let changed = format!("You are now playing {} {}", class.name(), class.emoji());
+                            let player = playthrough.players.iter().find(|player| player.user_id == user.id).expect("player is in playthrough");
+                            match playthrough.started {
+                                Some(_) => match send_loadouts(ctx, playthrough, player, &loadouts, &preferences).await {
+                                    (_, Ok(())) => changed,
+                                    (_, Err(_)) => format!("{changed}, but I couldn't send you the new loadouts. Please check your DMs are open"),
+                                },
+                                None => changed,
+                            }
+                        },
+                        Err(ChangeClassError::ClassTaken) => format!("Someone is already playing {} in this playthrough", class.name()),
+                        Err(ChangeClassError::NotInPlaythrough) => str!("You are not in a playthrough"),
+                    },
+                    None => str!("That class doesn't exist"),
+                }
+            },
+            _ => str!("This button doesn't do anything anymore"),
+        }
+    };
+
+    interaction.edit_response(ctx, EditInteractionResponse::new().content(content)).await?;
+    if let Some(playthrough) = playthroughs.active_playthroughs.get(&owner) {
+        refresh_dashboard(ctx, playthrough).await;
+    }
 
     Ok(())
 }
@@ -75,7 +317,7 @@ async fn create(ctx: Context<'_>, #[description = "The class you're playing in t
     ctx.defer().await?;
 
     let mut playthroughs = ctx.data().playthroughs.write().await;
-    match playthroughs.create(ctx.author(), class, &ctx.data().pool).await {
+    match playthroughs.create(ctx.author(), class, ctx.guild_id(), &ctx.data().pool).await {
         Ok(_) => ctx.say("Successfully created a new playthrough").await?,
         Err(InPlaythroughError) => ctx.say("You are already in a playthrough!").await?,
     };
@@ -84,47 +326,17 @@ async fn create(ctx: Context<'_>, #[description = "The class you're playing in t
 }
 
 #[command(slash_command, description_localized("en-US", "Ends the playthrough you're in"))]
-async fn end(ctx: Context<'_>) -> PoiseResult {
+async fn end(
+    ctx: Context<'_>,
+    #[description = "Also DMs the playthrough's recap to every player"] dm_recap: Option<bool>,
+) -> PoiseResult {
     ctx.defer().await?;
 
     let mut playthroughs = ctx.data().playthroughs.write().await;
     match playthroughs.end(ctx.author(), &ctx.data().pool).await {
         Ok(playthrough) => {
-            let time_spent = playthrough.started
-                .map(|started| {
-                    macro_rules! find_highest {
-                        (($f: expr, $t: literal), ($l: expr, $n: literal)) => {{
-                            if $f > 0 {
-                                format!("{} {}", $f, $t)
-                            } else {
-                                format!("{} {}", $l, $n)
-                            }
-                        }};
-                        (($f: expr, $t: literal), ($l: expr, $n: literal), $(($a: expr, $o: expr)),+) => {{
-                            if $f > 0 {
-                                format!("{} {}", $f, $t)
-                            }
-                            $(
-                                else if $a > 0 {
-                                    format!("{} {}", $a, $o)
-                                }
-                             )*
-                            else {
-                                format!("{} {}", $l, $n)
-                            }
-                        }};
-                    }
-
-                    let duration = Utc::now().naive_utc() - started;
-                    find_highest!(
-                        (duration.num_days(), "days"),
-                        (duration.num_seconds(), "seconds"),
-                        (duration.num_hours(), "hours"),
-                        (duration.num_minutes(), "minutes")
-                    )
-                })
-                .unwrap_or(str!("Playthrough never started"));
-            ctx.say(format!("Successfully ended your playthrough\nTotal playthrough time: {time_spent}")).await?
+            let embed = finish_playthrough(ctx, &playthrough, dm_recap.unwrap_or_default(), &ctx.data().pool).await;
+            ctx.send(CreateReply::default().content("Successfully ended your playthrough").embed(embed)).await?
         },
         Err(FinishPlaythroughError::NotOwner) => ctx.say("You are not the owner of the playthrough you are in").await?,
         Err(FinishPlaythroughError::NotInPlaythrough) => ctx.say("You are not in a playthrough").await?,
@@ -133,6 +345,28 @@ async fn end(ctx: Context<'_>) -> PoiseResult {
     Ok(())
 }
 
+/// Cleans up after a playthrough that ended, posting its recap and returning the recap's embed
+async fn finish_playthrough(http: impl CacheHttp, playthrough: &Playthrough, dm_recap: bool, pool: &sqlx::PgPool) -> CreateEmbed {
+    let http = http.http();
+    let player_names = join_all(playthrough.players.iter()
+        .map(|player| async move { player.user_id.to_user(http).await.expect("player is user").name })).await;
+    let now = Utc::now().naive_utc();
+    let recap = Recap::new(playthrough, player_names, now);
+    let token = recap.save(pool).await;
+    let embed = recap.create_embed(&token);
+
+    announce(http, playthrough, PlaythroughEvent::Ended(playthrough.active_time(now))).await;
+    announce_embed(http, playthrough, embed.clone()).await;
+    if dm_recap {
+        join_all(playthrough.players.iter()
+            .map(|player| player.user_id.direct_message(http, CreateMessage::new().embed(embed.clone())))).await;
+    }
+    roles::delete(http, playthrough).await;
+    close_dashboard(http, playthrough).await;
+
+    embed
+}
+
 #[command(slash_command, description_localized("en-US", "Starts your created playthrough"))]
 async fn start(ctx: Context<'_>) -> PoiseResult {
     ctx.defer().await?;
@@ -140,12 +374,15 @@ async fn start(ctx: Context<'_>) -> PoiseResult {
     let data = ctx.data();
     let mut playthroughs = data.playthroughs.write().await;
     let loadouts = data.loadouts.read().await;
+    let preferences = data.preferences.read().await;
 
     match playthroughs.start(ctx.author(), &ctx.data().pool).await {
         Ok(()) => {
             let playthrough = playthroughs.active_playthroughs.get(&ctx.author().id).expect("thing exists");
+            announce(ctx, playthrough, PlaythroughEvent::Started).await;
+            refresh_dashboard(ctx, playthrough).await;
             let error_futures = {
-                let dm_results = resend_loadouts(ctx, playthrough, &loadouts).await;
+                let dm_results = resend_loadouts(ctx, playthrough, &loadouts, &preferences).await;
                 dm_results.into_iter().map(|(user, dm_res)| async move {
                     if dm_res.is_err() {
                         ctx.say(format!("{user}, I can't DM you! Please enable DMs if you want me to automatically send you loadouts!")).await
@@ -173,11 +410,18 @@ async fn join(
     ctx.defer().await?;
 
     let mut playthroughs = ctx.data().playthroughs.write().await;
-    match playthroughs.join_player(&owner, Player { user_id: ctx.author().id, class }, &ctx.data().pool).await {
-        Ok(()) => ctx.say(format!("Successfully joined {}'s playthrough", owner)).await?,
+    match playthroughs.join_player(&owner, Player { user_id: ctx.author().id, class, stage: None }, &ctx.data().pool).await {
+        Ok(()) => {
+            let playthrough = playthroughs.active_playthroughs.get(&owner.id).expect("joined playthrough exists");
+            announce(ctx, playthrough, PlaythroughEvent::Joined(ctx.author().id, class)).await;
+            roles::add_player(ctx, playthrough, ctx.author().id).await;
+            refresh_dashboard(ctx, playthrough).await;
+            ctx.say(format!("Successfully joined {}'s playthrough", owner)).await?
+        },
         Err(JoinPlayerError::PlayerNotInPlaythrough) => ctx.say("That player is not in a playthrough").await?,
         Err(JoinPlayerError::PlayerNotOwner) => ctx.say("That player is not the owner of the playthrough they are in").await?,
         Err(JoinPlayerError::AlreadyInPlaythrough) => ctx.say("You are already in a playthrough").await?,
+        Err(JoinPlayerError::ClassTaken) => ctx.say(format!("Someone is already playing {} in that playthrough", class.name())).await?,
     };
 
     Ok(())
@@ -189,7 +433,13 @@ async fn kick(ctx: Context<'_>, #[description = "The player you want to kick"] p
 
     let mut playthroughs = ctx.data().playthroughs.write().await;
     match playthroughs.kick(ctx.author(), &player, &ctx.data().pool).await {
-        Ok(()) => ctx.say(format!("Successfully kicked {} from your playthrough", player)).await?,
+        Ok(()) => {
+            let playthrough = playthroughs.active_playthroughs.get(&ctx.author().id).expect("owner has a playthrough");
+            announce(ctx, playthrough, PlaythroughEvent::Kicked(player.id)).await;
+            roles::remove_player(ctx, playthrough, player.id).await;
+            refresh_dashboard(ctx, playthrough).await;
+            ctx.say(format!("Successfully kicked {} from your playthrough", player)).await?
+        },
         Err(KickError::NotOwner) => ctx.say("You are not the owner of the playthrough you are in").await?,
         Err(KickError::PlayerNotInPlaythrough) => ctx.say("That player is not in a playthrough").await?,
         Err(KickError::NotInPlaythrough) => ctx.say("You are not in a playthrough").await?,
@@ -205,7 +455,12 @@ async fn leave(ctx: Context<'_>) -> PoiseResult {
 
     let mut playthroughs = ctx.data().playthroughs.write().await;
     match playthroughs.leave(ctx.author(), &ctx.data().pool).await {
-        Ok(playthrough) => ctx.say(format!("Successfully left <@{}>'s playthrough", playthrough.owner)).await?,
+        Ok(playthrough) => {
+            announce(ctx, playthrough, PlaythroughEvent::Left(ctx.author().id)).await;
+            roles::remove_player(ctx, playthrough, ctx.author().id).await;
+            refresh_dashboard(ctx, playthrough).await;
+            ctx.say(format!("Successfully left <@{}>'s playthrough", playthrough.owner)).await?
+        },
         Err(LeaveError::NotInPlaythrough) => ctx.say("You are not in a playthrough").await?,
         Err(LeaveError::OwnerOfPlaythrough) => ctx.say("You cannot leave the playthrough you are an owner of").await?,
     };
@@ -217,16 +472,50 @@ async fn leave(ctx: Context<'_>) -> PoiseResult {
 async fn progress(
     ctx: Context<'_>,
     #[description = "The new stage to progress to. Leaving this blank advances the stage by 1"] stage: Option<Stage>,
+    #[description = "Only changes your own stage instead of the whole playthrough's"] just_me: Option<bool>,
 ) -> PoiseResult {
     ctx.defer().await?;
 
     let mut playthroughs = ctx.data().playthroughs.write().await;
     let loadouts = ctx.data().loadouts.read().await;
+    let preferences = ctx.data().preferences.read().await;
 
-    match playthroughs.progress(ctx.author(), stage, &ctx.data().pool).await {
+    if just_me.unwrap_or_default() {
+        let old_stage = playthroughs.get_playthrough(ctx.author().id)
+            .and_then(|playthrough| Some(playthrough.stage_of(playthrough.players.iter().find(|p| p.user_id == ctx.author().id)?)))
+            .unwrap_or_default();
+        match playthroughs.progress_player(ctx.author(), stage, &ctx.data().pool).await {
+            Ok(playthrough) => {
+                let player = playthrough.players.iter().find(|p| p.user_id == ctx.author().id).expect("player is in their playthrough");
+                announce(ctx, playthrough, PlaythroughEvent::PlayerProgressed(player.user_id, old_stage)).await;
+                refresh_dashboard(ctx, playthrough).await;
+                if playthrough.started.is_some() {
+                    let _ = send_loadouts(ctx, playthrough, player, &loadouts, &preferences).await;
+                }
+                match player.stage {
+                    Some(stage) => ctx.say(format!("Progressed you to stage `{}`", stage.name())).await?,
+                    None => ctx.say(format!("You are back on the same stage as the rest of your playthrough (`{}`)", playthrough.stage.name())).await?,
+                }
+            },
+            // any player can progress themselves, so only being outside a playthrough can stop them
+            Err(ProgressError::NotInPlaythrough | ProgressError::NotOwner) => ctx.say("You are not in a playthrough").await?,
+            Err(ProgressError::LastStage) => ctx.say("You are already on the last stage of the game").await?,
+        };
+        return Ok(());
+    }
+
+    let author = ctx.author().id;
+    if playthroughs.get_playthrough(author).is_some_and(|playthrough| playthrough.owner != author && playthrough.settings.vote_majority.is_some()) {
+        return start_progress_vote(ctx, &mut playthroughs, stage, &loadouts, &preferences).await;
+    }
+
+    let old_stage = playthroughs.get_playthrough(ctx.author().id).map(|playthrough| playthrough.stage).unwrap_or_default();
+    match playthroughs.progress(ctx.author().id, stage, &ctx.data().pool).await {
         Ok(playthrough) => {
+            announce(ctx, playthrough, PlaythroughEvent::Progressed(old_stage)).await;
+            refresh_dashboard(ctx, playthrough).await;
             if playthrough.started.is_some() {
-                resend_loadouts(ctx, playthrough, &loadouts).await;
+                resend_loadouts(ctx, playthrough, &loadouts, &preferences).await;
             }
             let progress_str = format!("Progressed to stage `{}`", playthrough.stage.name());
             if playthrough.started.is_some() {
@@ -246,17 +535,775 @@ async fn progress(
     Ok(())
 }
 
-async fn resend_loadouts(http: impl CacheHttp, playthrough: &Playthrough, loadouts: &LoadoutData) -> Vec<(User, StdResult<(), poise::serenity_prelude::Error>)> {
-    let dm_futures = playthrough.players.iter().map(|player| {
-        let http = http.http();
-        async move {
-            let user = player.user_id.to_user(&http).await.expect("player id is a user");
-            let stage_data = loadouts.get_stage(playthrough.stage).expect("loadout exists");
-            let dm_res = user.direct_message(&http, CreateMessage::new()
-                .embed(stage_data.create_embed(&user, player.class, playthrough.stage))).await.map(|_| ());
-            (user, dm_res)
+/// Posts a poll for the players of the author's playthrough to vote on progressing it to `stage`, or the next stage if `None`
+async fn start_progress_vote(
+    ctx: Context<'_>,
+    playthroughs: &mut PlaythroughData,
+    stage: Option<Stage>,
+    loadouts: &LoadoutData,
+    preferences: &PreferenceData,
+) -> PoiseResult {
+    let playthrough = playthroughs.get_playthrough(ctx.author().id).expect("author is in a playthrough");
+    let owner = playthrough.owner;
+    let new_stage = match new_vote_stage(playthrough, stage) {
+        Ok(new_stage) => new_stage,
+        Err(message) => {
+            ctx.say(message).await?;
+            return Ok(());
+        },
+    };
+
+    let reply = ctx.say(format!("Opening a vote to progress to `{}`...", new_stage.name())).await?;
+    let vote = ProgressVote {
+        stage: new_stage,
+        started_by: ctx.author().id,
+        channel: ctx.channel_id(),
+        message: reply.message().await?.id,
+        expires_at: Utc::now().naive_utc() + vote::vote_duration(),
+        ballots: Vec::new(),
+    };
+
+    match playthroughs.start_vote(ctx.author(), vote, &ctx.data().pool).await {
+        Ok(outcome) => {
+            let (embed, components) = conclude_vote(ctx, playthroughs, owner, outcome, loadouts, preferences, &ctx.data().pool).await;
+            reply.edit(ctx, CreateReply::default().content("").embed(embed).components(components)).await?;
+        },
+        Err(err) => { reply.edit(ctx, CreateReply::default().content(start_vote_error(err))).await?; },
+    }
+
+    Ok(())
+}
+
+/// The stage a new vote in `playthrough` would progress to (the next stage if `stage` is `None`), or why a vote can't be opened
+fn new_vote_stage(playthrough: &Playthrough, stage: Option<Stage>) -> StdResult<Stage, &'static str> {
+    if let Err(StartVoteError::VoteInProgress) = playthrough.vote_majority() {
+        return Err("There is already a vote to progress your playthrough");
+    }
+    stage.or_else(|| FromPrimitive::from_usize(playthrough.stage as usize + 1))
+        .ok_or("Your playthrough is already on the last stage of the game")
+}
+
+fn start_vote_error(err: StartVoteError) -> &'static str {
+    match err {
+        StartVoteError::NotInPlaythrough => "You are not in a playthrough",
+        StartVoteError::VotingDisabled => "Voting to progress is turned off in your playthrough",
+        StartVoteError::VoteInProgress => "There is already a vote to progress your playthrough",
+    }
+}
+
+/// Handles the buttons of a vote to progress, which have the custom id `vote-{yes|no}-{owner}`
+pub async fn handle_vote_interaction(ctx: &serenity::Context, interaction: &ComponentInteraction, data: &Data) -> PoiseResult {
+    let mut args = interaction.data.custom_id.split('-').skip(1);
+    let approve = args.next() == Some("yes");
+    let owner = args.next().and_then(|id| id.parse().ok()).map(UserId::new).ok_or("invalid vote custom id")?;
+
+    let mut playthroughs = data.playthroughs.write().await;
+    let loadouts = data.loadouts.read().await;
+    let preferences = data.preferences.read().await;
+
+    let error = match playthroughs.cast_vote(owner, interaction.user.id, approve, interaction.message.id, &data.pool).await {
+        Ok(outcome) => {
+            let (embed, components) = conclude_vote(ctx, &mut playthroughs, owner, outcome, &loadouts, &preferences, &data.pool).await;
+            interaction.create_response(ctx, CreateInteractionResponse::UpdateMessage(CreateInteractionResponseMessage::new()
+                .embed(embed)
+                .components(components)
+            )).await?;
+            return Ok(());
+        },
+        Err(CastVoteError::NoVote) => "This vote is already over",
+        Err(CastVoteError::NotPlayer) => "Only players in this playthrough can vote",
+    };
+    interaction.create_response(ctx, CreateInteractionResponse::Message(CreateInteractionResponseMessage::new()
+        .content(error)
+        .ephemeral(true)
+    )).await?;
+
+    Ok(())
+}
+
+/// Progresses `owner`'s playthrough if its vote passed and closes the vote if it is over, returning the vote's updated embed and
+/// buttons
+async fn conclude_vote(
+    http: impl CacheHttp,
+    playthroughs: &mut PlaythroughData,
+    owner: UserId,
+    outcome: VoteOutcome,
+    loadouts: &LoadoutData,
+    preferences: &PreferenceData,
+    pool: &sqlx::PgPool,
+) -> (CreateEmbed, Vec<CreateActionRow>) {
+    if let VoteOutcome::Pending { .. } | VoteOutcome::Expired = outcome {
+        let vote = playthroughs.active_playthroughs.get(&owner).and_then(|playthrough| playthrough.vote.as_ref()).expect("vote is open");
+        return (vote.create_embed(owner, outcome), ProgressVote::create_buttons(owner));
+    }
+
+    let vote = playthroughs.finish_vote(owner, pool).await.expect("vote is open");
+    let old_stage = playthroughs.active_playthroughs.get(&owner).map(|playthrough| playthrough.stage).unwrap_or_default();
+    // the playthrough may have already moved past the vote's stage some other way
+    if outcome == VoteOutcome::Passed && vote.stage as usize > old_stage as usize {
+        if let Ok(playthrough) = playthroughs.progress(owner, Some(vote.stage), pool).await {
+            announce(&http, playthrough, PlaythroughEvent::Progressed(old_stage)).await;
+            refresh_dashboard(&http, playthrough).await;
+            if playthrough.started.is_some() {
+                resend_loadouts(&http, playthrough, loadouts, preferences).await;
+            }
+        }
+    }
+
+    (vote.create_embed(owner, outcome), Vec::new())
+}
+
+#[command(slash_command, description_localized("en-US", "Pauses your playthrough so the time away doesn't count towards its play time"))]
+async fn pause(ctx: Context<'_>) -> PoiseResult {
+    ctx.defer().await?;
+
+    let mut playthroughs = ctx.data().playthroughs.write().await;
+    match playthroughs.pause(ctx.author(), &ctx.data().pool).await {
+        Ok(()) => {
+            refresh_dashboard(ctx, playthroughs.get_playthrough(ctx.author().id).expect("paused playthrough exists")).await;
+            ctx.say("Paused your playthrough. Resume it with `/playthrough resume`").await?
+        },
+        Err(PauseError::NotInPlaythrough) => ctx.say("You are not in a playthrough").await?,
+        Err(PauseError::NotOwner) => ctx.say("You are not the owner of the playthrough you are in").await?,
+        Err(PauseError::NotStarted) => ctx.say("This playthrough hasn't started yet").await?,
+        Err(PauseError::AlreadyPaused) => ctx.say("This playthrough is already paused").await?,
+    };
+
+    Ok(())
+}
+
+#[command(slash_command, description_localized("en-US", "Resumes your paused playthrough"))]
+async fn resume(ctx: Context<'_>) -> PoiseResult {
+    ctx.defer().await?;
+
+    let mut playthroughs = ctx.data().playthroughs.write().await;
+    match playthroughs.resume(ctx.author(), &ctx.data().pool).await {
+        Ok(paused_for) => {
+            refresh_dashboard(ctx, playthroughs.get_playthrough(ctx.author().id).expect("resumed playthrough exists")).await;
+            ctx.say(format!("Resumed your playthrough after {}", format_duration(paused_for))).await?
+        },
+        Err(ResumeError::NotInPlaythrough) => ctx.say("You are not in a playthrough").await?,
+        Err(ResumeError::NotOwner) => ctx.say("You are not the owner of the playthrough you are in").await?,
+        Err(ResumeError::AlreadyRunning) => ctx.say("This playthrough is not paused").await?,
+    };
+
+    Ok(())
+}
+
+#[command(slash_command, description_localized("en-US", "Reminds everyone in your playthrough about your next session"))]
+async fn remind(
+    ctx: Context<'_>,
+    #[description = "How long until the session, such as 2h or 1d 30m"] when: String,
+) -> PoiseResult {
+    ctx.defer().await?;
+
+    let Some(duration) = parse_duration(&when) else {
+        ctx.say("Invalid time! Try something like `2h`, `1d 30m` or `1w`").await?;
+        return Ok(());
+    };
+    let remind_at = Some(duration)
+        .filter(|duration| *duration <= TimeDelta::try_days(MAX_REMINDER_DAYS).expect("max reminder days is in range"))
+        .and_then(|duration| Utc::now().naive_utc().checked_add_signed(duration));
+    let Some(remind_at) = remind_at else {
+        ctx.say(format!("Reminders can be at most {MAX_REMINDER_DAYS} days away")).await?;
+        return Ok(());
+    };
+
+    let mut playthroughs = ctx.data().playthroughs.write().await;
+    match playthroughs.remind(ctx.author(), remind_at, &ctx.data().pool).await {
+        Ok(()) => ctx.say(format!("Everyone in your playthrough will be reminded <t:{}:R>", remind_at.and_utc().timestamp())).await?,
+        Err(RemindError::NotInPlaythrough) => ctx.say("You are not in a playthrough").await?,
+        Err(RemindError::NotOwner) => ctx.say("You are not the owner of the playthrough you are in").await?,
+    };
+
+    Ok(())
+}
+
+#[command(slash_command, guild_only, description_localized("en-US", "Sets where updates about your playthrough are posted"))]
+async fn channel(
+    ctx: Context<'_>,
+    #[description = "The channel to post updates in. Leaving this blank uses the current channel"] channel: Option<GuildChannel>,
+    #[description = "Creates a thread for your playthrough in the channel"] thread: Option<bool>,
+    #[description = "Stops posting updates about your playthrough"] disable: Option<bool>,
+) -> PoiseResult {
+    ctx.defer().await?;
+
+    let guild = ctx.guild_id().expect("command is guild only");
+    let channel = if disable.unwrap_or_default() {
+        None
+    } else {
+        let channel = channel.map(|channel| channel.id).unwrap_or(ctx.channel_id());
+        if thread.unwrap_or_default() {
+            let thread = channel.create_thread(ctx, CreateThread::new(format!("{}'s Playthrough", ctx.author().name))
+                .kind(ChannelType::PublicThread)).await;
+            match thread {
+                Ok(thread) => Some(thread.id),
+                Err(_) => {
+                    ctx.say(format!("I can't create threads in <#{channel}>!")).await?;
+                    return Ok(());
+                },
+            }
+        } else {
+            Some(channel)
+        }
+    };
+
+    let mut playthroughs = ctx.data().playthroughs.write().await;
+    match playthroughs.set_channel(ctx.author(), guild, channel, &ctx.data().pool).await {
+        Ok(_) => match channel {
+            Some(channel) => ctx.say(format!("Updates about your playthrough will now be posted in <#{channel}>")).await?,
+            None => ctx.say("Updates about your playthrough will no longer be posted").await?,
+        },
+        Err(SetChannelError::NotInPlaythrough) => ctx.say("You are not in a playthrough").await?,
+        Err(SetChannelError::NotOwner) => ctx.say("You are not the owner of the playthrough you are in").await?,
+    };
+
+    Ok(())
+}
+
+#[command(slash_command, guild_only, description_localized("en-US", "Creates or deletes a role for everyone in your playthrough"))]
+async fn role(
+    ctx: Context<'_>,
+    #[description = "Whether your playthrough should have a role"] enabled: bool,
+) -> PoiseResult {
+    ctx.defer().await?;
+
+    let guild = ctx.guild_id().expect("command is guild only");
+    let mut playthroughs = ctx.data().playthroughs.write().await;
+    let Some(playthrough) = playthroughs.active_playthroughs.get(&ctx.author().id) else {
+        let message = if playthroughs.all_users.contains(&ctx.author().id) {
+            "You are not the owner of the playthrough you are in"
+        } else {
+            "You are not in a playthrough"
+        };
+        ctx.say(message).await?;
+        return Ok(());
+    };
+
+    let role = match (enabled, playthrough.role) {
+        (true, Some((_, role))) => {
+            ctx.say(format!("Your playthrough already has a role, <@&{role}>")).await?;
+            return Ok(());
+        },
+        (false, None) => {
+            ctx.say("Your playthrough doesn't have a role").await?;
+            return Ok(());
+        },
+        (true, None) => match roles::create(ctx, guild, &ctx.author().name, playthrough).await {
+            Ok(role) => Some((guild, role)),
+            Err(_) => {
+                ctx.say("I can't create roles in this server! Please give me the Manage Roles permission").await?;
+                return Ok(());
+            },
+        },
+        (false, Some(_)) => {
+            roles::delete(ctx, playthrough).await;
+            None
+        },
+    };
+
+    match playthroughs.set_role(ctx.author(), role, &ctx.data().pool).await {
+        Ok(_) => match role {
+            Some((_, role)) => ctx.say(format!("Created <@&{role}> for your playthrough")).await?,
+            None => ctx.say("Deleted your playthrough's role").await?,
+        },
+        Err(SetRoleError::NotInPlaythrough) => ctx.say("You are not in a playthrough").await?,
+        Err(SetRoleError::NotOwner) => ctx.say("You are not the owner of the playthrough you are in").await?,
+    };
+
+    Ok(())
+}
+
+#[command(slash_command, description_localized("en-US", "Changes the settings of your playthrough"))]
+#[allow(clippy::too_many_arguments)]
+async fn settings(
+    ctx: Context<'_>,
+    #[description = "The difficulty mode"] difficulty: Option<Difficulty>,
+    #[description = "The name of the world"] #[max_length = 255] world_name: Option<String>,
+    #[description = "The size of the world"] world_size: Option<WorldSize>,
+    #[description = "The world seed"] #[max_length = 255] seed: Option<String>,
+    #[description = "The world evil"] evil: Option<WorldEvil>,
+    #[description = "Your tModLoader mod list (enabled.json in your Mods folder)"] mods: Option<Attachment>,
+    #[description = "Any notes about the playthrough"] #[max_length = 1000] notes: Option<String>,
+    #[description = "Prevents more than one player from playing the same class"] unique_classes: Option<bool>,
+    #[description = "Lets players vote to progress, passing when this percentage agrees. 0 turns voting off"] #[min = 0] #[max = 100]
+    vote_majority: Option<u8>,
+    #[description = "A setting to clear"] clear: Option<Setting>,
+) -> PoiseResult {
+    ctx.defer().await?;
+
+    let mods = match mods {
+        Some(mods) => match serde_json::from_slice::<Vec<String>>(&mods.download().await?) {
+            Ok(mods) => Some(mods),
+            Err(_) => {
+                ctx.say("Could not read your mod list! Please upload the `enabled.json` file in your tModLoader Mods folder").await?;
+                return Ok(());
+            },
+        },
+        None => None,
+    };
+
+    let mut playthroughs = ctx.data().playthroughs.write().await;
+    let update = |settings: &mut PlaythroughSettings| {
+        settings.difficulty = difficulty.or(settings.difficulty);
+        settings.world_name = world_name.or(settings.world_name.take());
+        settings.world_size = world_size.or(settings.world_size);
+        settings.seed = seed.or(settings.seed.take());
+        settings.evil = evil.or(settings.evil);
+        settings.mods = mods.unwrap_or(std::mem::take(&mut settings.mods));
+        settings.notes = notes.or(settings.notes.take());
+        settings.unique_classes = unique_classes.unwrap_or(settings.unique_classes);
+        if let Some(majority) = vote_majority {
+            settings.vote_majority = (majority > 0).then_some(majority);
+        }
+        if let Some(setting) = clear {
+            settings.clear(setting);
         }
-    });
-    join_all(dm_futures).await
+    };
+    match playthroughs.update_settings(ctx.author(), update, &ctx.data().pool).await {
+        Ok(playthrough) => {
+            refresh_dashboard(ctx, playthrough).await;
+            let mods = &playthrough.settings.mods;
+            let mut warnings = Vec::new();
+            if !mods.is_empty() && !mods.iter().any(|name| name == "CalamityMod") {
+                warnings.push(str!("Calamity isn't enabled in your mod list!"));
+            }
+            if playthrough.settings.unique_classes && playthrough.class_coverage().iter().any(|(_, players)| *players > 1) {
+                warnings.push(str!("Some classes are already played by more than one player. Only new players are affected"));
+            }
+            let unsupported = loadout_data::unsupported_mods(mods);
+            if !unsupported.is_empty() {
+                warnings.push(format!("The loadouts don't account for items from these mods: {}", unsupported.join(", ")));
+            }
+
+            ctx.send(CreateReply::default()
+                .content(if warnings.is_empty() { str!("Updated your playthrough's settings") } else { bulleted(&warnings) })
+                .embed(CreateEmbed::new()
+                    .title("Playthrough Settings")
+                    .fields(settings_fields(&playthrough.settings))
+                    .color(Color::FOOYOO)
+                    .timestamp(Timestamp::now())
+                )
+            ).await?
+        },
+        Err(UpdateSettingsError::NotInPlaythrough) => ctx.say("You are not in a playthrough").await?,
+        Err(UpdateSettingsError::NotOwner) => ctx.say("You are not the owner of the playthrough you are in").await?,
+    };
+
+    Ok(())
+}
+
+fn class_coverage(playthrough: &Playthrough) -> String {
+    playthrough.class_coverage().into_iter()
+        .map(|(class, players)| match players {
+            0 => format!("{} {}: Not covered", class.emoji(), class.name()),
+            1 => format!("{} {}: 1 player", class.emoji(), class.name()),
+            players => format!("{} {}: {players} players", class.emoji(), class.name()),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[command(slash_command, description_localized("en-US", "Suggests classes and support items for the playthrough you're in"))]
+async fn suggest(ctx: Context<'_>) -> PoiseResult {
+    let playthroughs = ctx.data().playthroughs.read().await;
+    let Some(playthrough) = playthroughs.get_playthrough(ctx.author().id) else {
+        ctx.say("You are not in a playthrough").await?;
+        return Ok(());
+    };
+    let loadouts = ctx.data().loadouts.read().await;
+    let Some(stage_data) = loadouts.get_stage(playthrough.stage) else {
+        ctx.say("No loadout found! Please report this!").await?;
+        return Ok(());
+    };
+
+    let coverage = playthrough.class_coverage();
+    let unfilled = coverage.iter()
+        .filter(|(_, players)| *players == 0)
+        .map(|(class, _)| match stage_data.main_weapon(*class) {
+            Some(weapon) => format!("{} **{}**, using the {weapon}", class.emoji(), class.name()),
+            None => format!("{} **{}**", class.emoji(), class.name()),
+        })
+        .collect::<Vec<_>>();
+
+    let support = stage_data.support_items().into_iter()
+        .map(|support| {
+            let players = playthrough.players.iter()
+                .filter(|player| player.class == support.class)
+                .map(|player| format!("<@{}>", player.user_id))
+                .collect::<Vec<_>>();
+            let provider = if players.is_empty() { format!("no {} yet", support.class.name()) } else { players.join(", ") };
+            format!("{} ({}): {provider}", support.item, support.kind)
+        })
+        .collect::<Vec<_>>();
+
+    let mut embed = CreateEmbed::new()
+        .title(format!("Suggestions for {}", playthrough.stage.name()))
+        .thumbnail(playthrough.stage.img())
+        .field("Class Coverage", class_coverage(playthrough), true)
+        .field("Unfilled Classes", if unfilled.is_empty() { str!("Every class is covered!") } else { bulleted(&unfilled) }, true)
+        .field("Shared Support", if support.is_empty() { str!("None this stage") } else { bulleted(&support) }, false)
+        .color(Color::FOOYOO)
+        .footer(CreateEmbedFooter::new("Loadouts by GitGudWO").icon_url(crate::get_asset("gitgudpfp.jpg")))
+        .timestamp(Timestamp::now());
+    let duplicates = coverage.iter().filter(|(_, players)| *players > 1).map(|(class, _)| class.name()).collect::<Vec<_>>();
+    if !duplicates.is_empty() {
+        embed = embed.description(format!("More than one player is playing {}. Consider switching to an unfilled class to cover more \
+            support items", duplicates.join(", ")));
+    }
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+fn settings_fields(settings: &PlaythroughSettings) -> Vec<(&'static str, String, bool)> {
+    let world = [
+        settings.world_name.as_ref().map(|name| format!("Name: {name}")),
+        settings.world_size.map(|size| format!("Size: {}", size.name())),
+        settings.seed.as_ref().map(|seed| format!("Seed: `{seed}`")),
+        settings.evil.map(|evil| format!("Evil: {}", evil.name())),
+    ].into_iter().flatten().collect::<Vec<_>>();
+
+    let mut fields = Vec::new();
+    if let Some(difficulty) = settings.difficulty {
+        fields.push(("Difficulty", difficulty.name().to_string(), true));
+    }
+    if settings.unique_classes {
+        fields.push(("Unique Classes", str!("Yes"), true));
+    }
+    if let Some(majority) = settings.vote_majority {
+        fields.push(("Progress Votes", format!("{majority}% of players"), true));
+    }
+    if !world.is_empty() {
+        fields.push(("World", world.join("\n"), true));
+    }
+    if !settings.mods.is_empty() {
+        // embed fields can only be 1024 characters long
+        let mut shown = 0;
+        let mut length = 0;
+        for name in &settings.mods {
+            if length + name.len() + 2 > 950 { break }
+            length += name.len() + 2;
+            shown += 1;
+        }
+        let mut mods = settings.mods[..shown].join(", ");
+        if shown < settings.mods.len() {
+            mods += &format!(" and {} more", settings.mods.len() - shown);
+        }
+        fields.push(("Mods", format!("{} enabled\n{mods}", settings.mods.len()), false));
+    }
+    if let Some(notes) = &settings.notes {
+        fields.push(("Notes", notes.clone(), false));
+    }
+    fields
+}
+
+#[command(slash_command, description_localized("en-US", "Lists your playthrough on the looking-for-group board"))]
+async fn public(
+    ctx: Context<'_>,
+    #[description = "A short description of your playthrough"] #[max_length = 255] description: String,
+    #[description = "A comma separated list of the classes you're looking for. Leave blank to accept any class"] classes: Option<String>,
+) -> PoiseResult {
+    ctx.defer().await?;
+
+    let Some(wanted_classes) = CalamityClass::parse_list(classes.as_deref().unwrap_or_default()) else {
+        ctx.say("Invalid class list! Try something like `Ranger, Rogue`").await?;
+        return Ok(());
+    };
+
+    let listing = Listing { owner_name: ctx.author().name.clone(), description, wanted_classes, listed_at: Utc::now().naive_utc() };
+    let mut playthroughs = ctx.data().playthroughs.write().await;
+    match playthroughs.set_listing(ctx.author(), Some(listing), &ctx.data().pool).await {
+        Ok(_) => ctx.say("Your playthrough is now public! Other players can find it with `/lfg`").await?,
+        Err(SetListingError::NotInPlaythrough) => ctx.say("You are not in a playthrough").await?,
+        Err(SetListingError::NotOwner) => ctx.say("You are not the owner of the playthrough you are in").await?,
+    };
+
+    Ok(())
+}
+
+#[command(slash_command, description_localized("en-US", "Removes your playthrough from the looking-for-group board"))]
+async fn private(ctx: Context<'_>) -> PoiseResult {
+    ctx.defer().await?;
+
+    let mut playthroughs = ctx.data().playthroughs.write().await;
+    match playthroughs.set_listing(ctx.author(), None, &ctx.data().pool).await {
+        Ok(_) => ctx.say("Your playthrough is now private").await?,
+        Err(SetListingError::NotInPlaythrough) => ctx.say("You are not in a playthrough").await?,
+        Err(SetListingError::NotOwner) => ctx.say("You are not the owner of the playthrough you are in").await?,
+    };
+
+    Ok(())
+}
+
+#[command(slash_command, description_localized("en-US", "Marks a boss as defeated in the playthrough you're in"))]
+async fn defeated(
+    ctx: Context<'_>,
+    #[description = "The boss that was defeated"] #[autocomplete = "autocomplete_boss"] boss: String,
+) -> PoiseResult {
+    ctx.defer().await?;
+
+    let Some(boss) = Boss::from_name(&boss) else {
+        ctx.say(format!("`{boss}` is not a boss")).await?;
+        return Ok(());
+    };
+
+    let mut playthroughs = ctx.data().playthroughs.write().await;
+    let loadouts = ctx.data().loadouts.read().await;
+    let preferences = ctx.data().preferences.read().await;
+
+    match playthroughs.defeat(ctx.author(), boss, &ctx.data().pool).await {
+        Ok((_, None)) => ctx.say(format!("Marked `{boss}` as defeated")).await?,
+        Ok((playthrough, Some(old_stage))) => {
+            announce(ctx, playthrough, PlaythroughEvent::Progressed(old_stage)).await;
+            refresh_dashboard(ctx, playthrough).await;
+            if playthrough.started.is_some() {
+                resend_loadouts(ctx, playthrough, &loadouts, &preferences).await;
+            }
+            ctx.say(format!("Marked `{boss}` as defeated\nEvery boss in the previous stage has been defeated! Progressed to stage `{}`",
+                playthrough.stage.name())).await?
+        },
+        Err(DefeatBossError::NotInPlaythrough) => ctx.say("You are not in a playthrough").await?,
+        Err(DefeatBossError::AlreadyDefeated) => ctx.say(format!("`{boss}` has already been defeated in this playthrough")).await?,
+    };
+
+    Ok(())
+}
+
+#[command(slash_command, description_localized("en-US", "Views which bosses have been defeated in a playthrough"))]
+async fn checklist(
+    ctx: Context<'_>,
+    #[description = "The user's playthrough to check"] #[rename = "user"] other: Option<User>
+) -> PoiseResult {
+    let user = other.as_ref().unwrap_or(ctx.author());
+
+    let playthroughs = ctx.data().playthroughs.read().await;
+    let Some(playthrough) = playthroughs.get_playthrough(user.id) else {
+        ctx.say(format!("{} not currently in a playthrough", other.map(|_| "That user is").unwrap_or("You are"))).await?;
+        return Ok(());
+    };
+
+    let owner = playthrough.owner.to_user(ctx).await.expect("owner is a user");
+    let stages = Stage::all()
+        .filter(|stage| !stage.bosses().is_empty())
+        .map(|stage| {
+            let bosses = stage.bosses().into_iter()
+                .map(|boss| format!("{} {boss}", if playthrough.defeated.contains(&boss) { ":white_check_mark:" } else { ":black_large_square:" }))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let name = if stage == playthrough.stage { format!("{} (Current)", stage.name()) } else { stage.name().to_string() };
+            (name, bosses, true)
+        });
+
+    ctx.send(CreateReply::default()
+        .embed(CreateEmbed::new()
+            .title(format!("{}'s Boss Checklist", owner.name))
+            .thumbnail(owner.avatar_url().unwrap_or_default())
+            .fields(stages)
+            .color(Color::FOOYOO)
+            .footer(CreateEmbedFooter::new("Loadouts by GitGudWO").icon_url(crate::get_asset("gitgudpfp.jpg")))
+            .timestamp(Timestamp::now())
+        )
+    ).await?;
+
+    Ok(())
+}
+
+#[command(slash_command, rename = "sync-world", description_localized("en-US", "Detects your playthrough's progression from a world file"))]
+async fn sync_world(
+    ctx: Context<'_>,
+    #[description = "Your world file (.wld)"] world: Attachment,
+    #[description = "Your tModLoader world file (.twld), used to detect Calamity bosses"] modded: Option<Attachment>,
+) -> PoiseResult {
+    ctx.defer().await?;
+
+    let world = match WorldFile::parse(&world.download().await?) {
+        Ok(world) => world,
+        Err(err) => {
+            ctx.say(format!("Could not read your world file: {err}")).await?;
+            return Ok(());
+        },
+    };
+    let modded = match modded {
+        Some(modded) => match world_file::parse_modded_flags(&modded.download().await?) {
+            Ok(defeated) => Some(defeated),
+            Err(err) => {
+                ctx.say(format!("Could not read your tModLoader world file: {err}")).await?;
+                return Ok(());
+            },
+        },
+        None => None,
+    };
+
+    let current_stage = {
+        let playthroughs = ctx.data().playthroughs.read().await;
+        match playthroughs.active_playthroughs.get(&ctx.author().id) {
+            Some(playthrough) => playthrough.stage,
+            None if playthroughs.all_users.contains(&ctx.author().id) => {
+                ctx.say("You are not the owner of the playthrough you are in").await?;
+                return Ok(());
+            },
+            None => {
+                ctx.say("You are not in a playthrough").await?;
+                return Ok(());
+            },
+        }
+    };
+
+    let include_modded = modded.is_some();
+    let defeated = [world.defeated.clone(), modded.unwrap_or_default()].concat();
+    let detected_stage = Stage::PreBoss.furthest(&defeated, include_modded);
+
+    let mut embed = CreateEmbed::new()
+        .title(format!("World: {}", world.name))
+        .thumbnail(detected_stage.img())
+        .field("World", format!("Seed: `{}`\nSize: {}x{}\nMode: {}\nEvil: {}\nHardmode: {}", world.seed, world.width, world.height,
+            world.game_mode_name(), if world.crimson { "Crimson" } else { "Corruption" }, if world.hard_mode { "Yes" } else { "No" }), false)
+        .field("Detected Stage", detected_stage.name(), true)
+        .field("Current Stage", current_stage.name(), true)
+        .field("Defeated Bosses", if defeated.is_empty() { str!("None") } else { bulleted(&defeated) }, false)
+        .color(Color::FOOYOO)
+        .timestamp(Timestamp::now());
+    if !include_modded {
+        embed = embed.description("Only vanilla bosses were checked. Upload your `.twld` file as well to detect Calamity bosses");
+    }
+
+    if detected_stage as usize <= current_stage as usize {
+        ctx.send(CreateReply::default()
+            .content("Your playthrough is already at or past the stage detected in this world")
+            .embed(embed)
+        ).await?;
+        return Ok(());
+    }
+
+    let reply = ctx.send(CreateReply::default()
+        .content(format!("Do you want to progress your playthrough to `{}`?", detected_stage.name()))
+        .embed(embed.clone())
+        .components(vec![CreateActionRow::Buttons(vec![
+            CreateButton::new("sync-world-confirm").style(ButtonStyle::Success).label("Progress"),
+            CreateButton::new("sync-world-cancel").style(ButtonStyle::Secondary).label("Cancel"),
+        ])])
+    ).await?;
+    let message = reply.message().await?;
+
+    let interaction = message.await_component_interaction(&ctx.serenity_context().shard)
+        .author_id(ctx.author().id)
+        .timeout(Duration::from_secs(60))
+        .await;
+    let Some(interaction) = interaction.filter(|interaction| interaction.data.custom_id == "sync-world-confirm") else {
+        reply.edit(ctx, CreateReply::default().content("Did not progress your playthrough").embed(embed).components(Vec::new())).await?;
+        return Ok(());
+    };
+
+    let mut playthroughs = ctx.data().playthroughs.write().await;
+    let loadouts = ctx.data().loadouts.read().await;
+    let preferences = ctx.data().preferences.read().await;
+    for boss in &defeated {
+        let _ = playthroughs.defeat(ctx.author(), *boss, &ctx.data().pool).await;
+    }
+    let stage = playthroughs.get_playthrough(ctx.author().id).map(|playthrough| playthrough.stage);
+    let progress_res = match stage {
+        Some(stage) if stage as usize >= detected_stage as usize => Ok(playthroughs.get_playthrough(ctx.author().id).expect("playthrough exists")),
+        _ => playthroughs.progress(ctx.author().id, Some(detected_stage), &ctx.data().pool).await,
+    };
+
+    let content = match &progress_res {
+        Ok(playthrough) => format!("Progressed to stage `{}`", playthrough.stage.name()),
+        Err(ProgressError::NotInPlaythrough) => str!("You are not in a playthrough"),
+        Err(ProgressError::NotOwner) => str!("You are not the owner of the playthrough you are in"),
+        Err(ProgressError::LastStage) => str!("You are already on the last stage of the game"),
+    };
+    interaction.create_response(ctx, CreateInteractionResponse::UpdateMessage(CreateInteractionResponseMessage::new()
+        .content(content)
+        .components(Vec::new())
+    )).await?;
+
+    if let Ok(playthrough) = progress_res {
+        announce(ctx, playthrough, PlaythroughEvent::Progressed(current_stage)).await;
+        refresh_dashboard(ctx, playthrough).await;
+        if playthrough.started.is_some() {
+            resend_loadouts(ctx, playthrough, &loadouts, &preferences).await;
+        }
+    }
+
+    Ok(())
+}
+
+async fn autocomplete_boss(_: Context<'_>, partial: &str) -> impl Iterator<Item = String> {
+    let partial = partial.to_lowercase();
+    Boss::all()
+        .filter(move |boss| boss.name().to_lowercase().contains(&partial))
+        .map(|boss| boss.name().to_string())
+        .take(25)
+        .collect::<Vec<_>>()
+        .into_iter()
+}
+
+/// Sends every player their loadouts for the stage they are on, following their delivery preferences. If a player wants DMs but has
+/// them closed, the loadouts are posted in the playthrough's announcement channel instead, and an error is only returned if neither
+/// could be sent
+async fn resend_loadouts(
+    http: impl CacheHttp,
+    playthrough: &Playthrough,
+    loadouts: &LoadoutData,
+    preferences: &PreferenceData,
+) -> Vec<(User, StdResult<(), poise::serenity_prelude::Error>)> {
+    let http = http.http();
+    join_all(playthrough.players.iter().map(|player| send_loadouts(http, playthrough, player, loadouts, preferences))).await
+}
+
+/// Sends a single player their loadouts, the same way as [`resend_loadouts`]
+async fn send_loadouts(
+    http: impl CacheHttp,
+    playthrough: &Playthrough,
+    player: &Player,
+    loadouts: &LoadoutData,
+    preferences: &PreferenceData,
+) -> (User, StdResult<(), poise::serenity_prelude::Error>) {
+    let http = http.http();
+    let preferences = preferences.get(player.user_id);
+    let stage = playthrough.stage_of(player);
+
+    let user = player.user_id.to_user(&http).await.expect("player id is a user");
+    let stage_data = loadouts.get_stage(stage).expect("loadout exists");
+    let classes: Vec<CalamityClass> = iter::once(player.class)
+        .chain(preferences.extra_classes.iter().copied().filter(|class| *class != player.class))
+        .collect();
+    let messages = |header: Option<String>| classes.iter().map(|class| {
+        let message = CreateMessage::new();
+        match (preferences.compact, &header) {
+            (true, Some(header)) => message.content(format!("{header}\n{}", stage_data.create_text(*class, stage))),
+            (true, None) => message.content(stage_data.create_text(*class, stage)),
+            (false, header) => message.content(header.clone().unwrap_or_default())
+                .embed(stage_data.create_embed(&user, *class, stage)),
+        }
+    }).collect::<Vec<_>>();
+
+    let dm_res = match (preferences.delivery, playthrough.channel) {
+        (Delivery::Off, _) => Ok(()),
+        (Delivery::Channel, Some(channel)) => send_all(http, channel, messages(Some(user.to_string()))).await,
+        // players who want their loadouts in the playthrough channel still get them as a DM until a channel is set
+        (Delivery::DirectMessage, _) | (Delivery::Channel, None) => {
+            let dm_res = match user.create_dm_channel(&http).await {
+                Ok(dm_channel) => send_all(http, dm_channel.id, messages(None)).await,
+                Err(err) => Err(err),
+            };
+            match (dm_res, playthrough.channel) {
+                (Err(_), Some(channel)) => {
+                    send_all(http, channel, messages(Some(format!("{user}, I can't DM you, so here's your loadout!")))).await
+                },
+                (dm_res, _) => dm_res,
+            }
+        },
+    };
+    (user, dm_res)
+}
+
+async fn send_all(http: &Http, channel: ChannelId, messages: Vec<CreateMessage>) -> StdResult<(), poise::serenity_prelude::Error> {
+    for message in messages {
+        channel.send_message(http, message).await?;
+    }
+    Ok(())
 }
 
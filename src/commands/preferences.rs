@@ -0,0 +1,103 @@
+use poise::{command, serenity_prelude::{Color, CreateEmbed, Timestamp}, ChoiceParameter, CreateReply};
+
+use crate::{loadout_data::CalamityClass, preferences::{Delivery, UserPreferences}, str, Context, PoiseResult};
+
+#[command(
+    slash_command,
+    subcommands("view", "delivery", "compact", "extra_class", "issue_updates", "public_reports"),
+    description_localized("en-US", "Changes how the bot sends you loadouts and updates"),
+)]
+pub async fn preferences(_: Context<'_>) -> PoiseResult {
+    Ok(())
+}
+
+#[command(slash_command, ephemeral, description_localized("en-US", "Views your current preferences"))]
+async fn view(ctx: Context<'_>) -> PoiseResult {
+    let preferences = ctx.data().preferences.read().await.get(ctx.author().id);
+    ctx.send(CreateReply::default().embed(create_embed(&preferences))).await?;
+
+    Ok(())
+}
+
+#[command(slash_command, ephemeral, description_localized("en-US", "Sets where your loadouts are sent when your playthrough progresses"))]
+async fn delivery(ctx: Context<'_>, #[description = "Where to send your loadouts"] delivery: Delivery) -> PoiseResult {
+    let mut preferences = ctx.data().preferences.write().await;
+    let preferences = preferences.update(ctx.author().id, &ctx.data().pool, |preferences| preferences.delivery = delivery).await;
+    ctx.send(CreateReply::default().content("Updated your preferences").embed(create_embed(preferences))).await?;
+
+    Ok(())
+}
+
+#[command(slash_command, ephemeral, description_localized("en-US", "Sends your loadouts as plain text instead of embeds"))]
+async fn compact(ctx: Context<'_>, #[description = "Whether to send loadouts as plain text"] enabled: bool) -> PoiseResult {
+    let mut preferences = ctx.data().preferences.write().await;
+    let preferences = preferences.update(ctx.author().id, &ctx.data().pool, |preferences| preferences.compact = enabled).await;
+    ctx.send(CreateReply::default().content("Updated your preferences").embed(create_embed(preferences))).await?;
+
+    Ok(())
+}
+
+#[command(
+    slash_command,
+    ephemeral,
+    rename = "extra-class",
+    description_localized("en-US", "Also sends you loadouts for a class other than the one you're playing"),
+)]
+async fn extra_class(
+    ctx: Context<'_>,
+    #[description = "The class"] class: CalamityClass,
+    #[description = "Whether to send loadouts for this class"] enabled: bool,
+) -> PoiseResult {
+    let mut preferences = ctx.data().preferences.write().await;
+    let preferences = preferences.update(ctx.author().id, &ctx.data().pool, |preferences| {
+        preferences.extra_classes.retain(|extra_class| *extra_class != class);
+        if enabled { preferences.extra_classes.push(class) }
+    }).await;
+    ctx.send(CreateReply::default().content("Updated your preferences").embed(create_embed(preferences))).await?;
+
+    Ok(())
+}
+
+#[command(
+    slash_command,
+    ephemeral,
+    rename = "issue-updates",
+    description_localized("en-US", "Sets whether you're messaged when an issue you reported is handled"),
+)]
+async fn issue_updates(ctx: Context<'_>, #[description = "Whether to message you about your reports"] enabled: bool) -> PoiseResult {
+    let mut preferences = ctx.data().preferences.write().await;
+    let preferences = preferences.update(ctx.author().id, &ctx.data().pool, |preferences| preferences.mute_issue_updates = !enabled).await;
+    ctx.send(CreateReply::default().content("Updated your preferences").embed(create_embed(preferences))).await?;
+
+    Ok(())
+}
+
+#[command(
+    slash_command,
+    ephemeral,
+    rename = "public-reports",
+    description_localized("en-US", "Sets whether your name is shown on your reports on the public issue tracker"),
+)]
+async fn public_reports(ctx: Context<'_>, #[description = "Whether to show your name on your reports"] enabled: bool) -> PoiseResult {
+    let mut preferences = ctx.data().preferences.write().await;
+    let preferences = preferences.update(ctx.author().id, &ctx.data().pool, |preferences| preferences.public_reports = enabled).await;
+    ctx.send(CreateReply::default().content("Updated your preferences").embed(create_embed(preferences))).await?;
+
+    Ok(())
+}
+
+fn create_embed(preferences: &UserPreferences) -> CreateEmbed {
+    let extra_classes = preferences.extra_classes.iter()
+        .map(|class| format!("{} {}", class.name(), class.emoji()))
+        .collect::<Vec<_>>();
+
+    CreateEmbed::new()
+        .title("Preferences")
+        .field("Delivery", preferences.delivery.name(), true)
+        .field("Format", if preferences.compact { "Plain Text" } else { "Embed" }, true)
+        .field("Extra Classes", if extra_classes.is_empty() { str!("None") } else { extra_classes.join("\n") }, true)
+        .field("Issue Updates", if preferences.mute_issue_updates { "Off" } else { "On" }, true)
+        .field("Public Reports", if preferences.public_reports { "Show Name" } else { "Anonymous" }, true)
+        .color(Color::FOOYOO)
+        .timestamp(Timestamp::now())
+}
@@ -1,25 +1,233 @@
-use poise::{command, serenity_prelude::CreateMessage};
+use std::{collections::HashSet, time::Duration};
 
-use crate::{Context, PoiseResult, loadout_data::{CalamityClass, Stage}};
+use poise::{command, serenity_prelude::{
+    ActionRowComponent,
+    Attachment,
+    CreateActionRow,
+    CreateAttachment,
+    CreateInputText,
+    CreateInteractionResponse,
+    CreateMessage,
+    CreateModal,
+    EditInteractionResponse,
+    EditMessage,
+    InputTextStyle,
+    ModalInteractionCollector,
+}, ChoiceParameter};
+use tracing::warn;
+
+use crate::{str, Context, PoiseResult, issue::{AddSupporterError, RateLimited}, loadout_data::{CalamityClass, Stage}};
+
+/// Discord's upload limit, which the screenshots have to fit in together since they are uploaded again with the issue's message
+const MAX_SCREENSHOTS_SIZE: u32 = 10 * 1024 * 1024;
 
 #[command(slash_command, description_localized("en-US", "Reports a problem with a loadout"), ephemeral)]
+#[allow(clippy::too_many_arguments)]
 pub async fn report(
     ctx: Context<'_>,
-    #[description = "The class that the issue is in"] class: CalamityClass,
     #[description = "The stage that the issue is in"] stage: Stage,
-    #[description = "The incorrect phrase"] incorrect: String,
-    #[description = "The phrase that should replace the incorrect one"] correct: String,
+    #[description = "The incorrect phrase"] #[autocomplete = "autocomplete_incorrect"] #[max_length = 255] incorrect: String,
+    #[description = "The phrase that should replace the incorrect one"] #[max_length = 255] correct: String,
+    #[description = "The class whose loadout the issue is in"] class: Option<CalamityClass>,
+    #[description = "Whether the issue is in the healing potion or powerups instead of a loadout"] stage_wide: Option<bool>,
+    #[description = "A screenshot showing the problem"] screenshot: Option<Attachment>,
+    #[description = "Another screenshot showing the problem"] second_screenshot: Option<Attachment>,
+    #[description = "Another screenshot showing the problem"] third_screenshot: Option<Attachment>,
 ) -> PoiseResult {
-    let mut issues = ctx.data().issues.write().await;
-    let issue = issues.create(ctx.author(), class, stage, incorrect, correct, &ctx.data().pool).await;
+    let poise::Context::Application(app_ctx) = ctx else { return Ok(()) };
+    let screenshots: Vec<Attachment> = [screenshot, second_screenshot, third_screenshot].into_iter().flatten().collect();
+
+    match (class, stage_wide.unwrap_or_default()) {
+        (Some(_), true) => {
+            ctx.say("Pick either a class or stage-wide, not both").await?;
+            return Ok(());
+        },
+        (None, false) => {
+            ctx.say("Pick the class whose loadout the issue is in, or stage-wide for the healing potion and powerups").await?;
+            return Ok(());
+        },
+        _ => {},
+    }
+
+    if let Some(block) = ctx.data().issues.read().await.blocked.get(&ctx.author().id) {
+        let reason = block.reason.as_ref().map(|reason| format!(": {reason}")).unwrap_or_default();
+        ctx.say(format!("You've been blocked from reporting issues{reason}")).await?;
+        return Ok(());
+    }
+    if screenshots.iter().any(|screenshot| !screenshot.content_type.as_ref().is_some_and(|kind| kind.starts_with("image/"))) {
+        ctx.say("Screenshots have to be images").await?;
+        return Ok(());
+    }
+    if screenshots.iter().map(|screenshot| screenshot.size).sum::<u32>() > MAX_SCREENSHOTS_SIZE {
+        ctx.say(format!("Screenshots can be at most {} MB together", MAX_SCREENSHOTS_SIZE / 1024 / 1024)).await?;
+        return Ok(());
+    }
+
+    let section = {
+        let loadouts = ctx.data().loadouts.read().await;
+        let Some(stage_data) = loadouts.get_stage(stage) else {
+            ctx.say(format!("There is no data for {}", stage.name())).await?;
+            return Ok(());
+        };
+        let fix = match class {
+            Some(class) => {
+                let Some(loadout) = stage_data.loadouts.get(&class) else {
+                    ctx.say(format!("There is no {} loadout for {}", class.name(), stage.name())).await?;
+                    return Ok(());
+                };
+                loadout.find_fix(&incorrect, &correct).ok_or_else(|| {
+                    let suggestion = loadout.suggest(&incorrect).map(|entry| format!(" Did you mean `{entry}`?")).unwrap_or_default();
+                    format!("`{incorrect}` isn't in the {} loadout for {}.{suggestion}", class.name(), stage.name())
+                })
+            },
+            None => stage_data.find_fix(&incorrect, &correct).ok_or_else(|| {
+                if stage_data.entries().iter().any(|(_, entry)| entry.contains(&incorrect)) {
+                    return format!("`{correct}` isn't a healing potion or powerup");
+                }
+                let suggestion = stage_data.suggest(&incorrect).map(|entry| format!(" Did you mean `{entry}`?")).unwrap_or_default();
+                format!("`{incorrect}` isn't the healing potion or a powerup for {}.{suggestion}", stage.name())
+            }),
+        };
+        match fix {
+            Ok(fix) => fix.section,
+            Err(error) => {
+                ctx.say(error).await?;
+                return Ok(());
+            },
+        }
+    };
+
+    {
+        let mut issues = ctx.data().issues.write().await;
+        if let Err(RateLimited { retry_at }) = issues.check_rate_limit(ctx.author().id, &ctx.data().report_limits, &ctx.data().pool).await {
+            ctx.say(format!("You've reported a lot of issues recently. You can report again <t:{}:R>", retry_at.and_utc().timestamp())).await?;
+            return Ok(());
+        }
+
+        if let Some(id) = issues.find_duplicate(class, stage, &incorrect) {
+            let (content, update) = match issues.add_supporter(id, ctx.author().id, &ctx.data().pool).await {
+                Ok(issue) => (
+                    format!("Someone has already reported this, so your +1 was added to issue {id:x}"),
+                    issue.message.map(|message| (message, issue.create_embed())),
+                ),
+                // the duplicate was just found, so the issue exists
+                Err(AddSupporterError::AlreadyReported | AddSupporterError::NoIssueFound) => (format!("You've already reported this as issue {id:x}"), None),
+            };
+            drop(issues);
 
-    ctx.data().issue_channel.send_message(ctx, CreateMessage::new()
-        .embed(issue.create_embed())
-        .components(issue.create_components()))
-        .await?;
+            if let Some(((channel, message), embed)) = update {
+                if let Err(err) = channel.edit_message(ctx, message, EditMessage::new().embed(embed)).await {
+                    warn!("could not show the +1 on issue {id:x}: {err}");
+                }
+            }
+            ctx.say(content).await?;
+            return Ok(());
+        }
+    }
 
-    ctx.say("Successfully reported your issue!").await?;
+    // the modal is collected here rather than in the event handler so the command's options are still around when it's submitted
+    let custom_id = format!("report-{}", app_ctx.interaction.id);
+    app_ctx.interaction.create_response(ctx, CreateInteractionResponse::Modal(
+        CreateModal::new(&custom_id, "Report an Issue").components(vec![CreateActionRow::InputText(
+            CreateInputText::new(InputTextStyle::Paragraph, "Explanation", "explanation")
+                .placeholder("Why is this phrase wrong? Links to the wiki help us check it")
+                .max_length(1000)
+                .required(false)
+        )])
+    )).await?;
+
+    let submission = ModalInteractionCollector::new(ctx.serenity_context())
+        .filter(move |modal| modal.data.custom_id == custom_id)
+        .timeout(Duration::from_secs(15 * 60))
+        .await;
+    let Some(submission) = submission else { return Ok(()) };
+    let explanation = submission.data.components.iter()
+        .flat_map(|row| &row.components)
+        .find_map(|component| match component {
+            ActionRowComponent::InputText(input) if input.custom_id == "explanation" => input.value.clone(),
+            _ => None,
+        })
+        .filter(|explanation| !explanation.trim().is_empty());
+    // downloading and uploading the screenshots can take longer than Discord waits for a response
+    submission.defer_ephemeral(ctx).await?;
+
+    // the reporter's attachments disappear along with the interaction, so the screenshots are uploaded again with the issue's message
+    let mut files = Vec::new();
+    for screenshot in &screenshots {
+        match screenshot.download().await {
+            Ok(bytes) => files.push(CreateAttachment::bytes(bytes, &screenshot.filename)),
+            Err(err) => {
+                warn!("could not download screenshot {}: {err}", screenshot.url);
+                submission.edit_response(ctx, EditInteractionResponse::new()
+                    .content(format!("Couldn't download `{}`, please try reporting again", screenshot.filename))
+                ).await?;
+                return Ok(());
+            },
+        }
+    }
+
+    let (id, embed, components) = {
+        let mut issues = ctx.data().issues.write().await;
+        let issue = issues.create(ctx.author(), class, stage, section, incorrect, correct, explanation, &ctx.data().pool).await;
+        (issue.id, issue.create_embed(), issue.create_components())
+    };
+
+    let message = ctx.data().issue_channel.send_message(ctx, CreateMessage::new().embed(embed).components(components).add_files(files)).await;
+    // the issue is saved either way, so it can still be found with /issues view when its message couldn't be posted
+    let content = match message {
+        Ok(message) => {
+            let attachments = message.attachments.iter().map(|attachment| attachment.filename.clone()).collect();
+            let mut issues = ctx.data().issues.write().await;
+            // the issue can only be gone if a sync replaced every issue in the meantime
+            if issues.set_message(id, message.channel_id, message.id, &ctx.data().pool).await.is_err()
+                || issues.set_attachments(id, attachments, &ctx.data().pool).await.is_err()
+            {
+                warn!("issue {id:x} was removed before its message was stored");
+            }
+            str!("Successfully reported your issue!")
+        },
+        Err(err) => {
+            warn!("could not post issue {id:x} in the issue channel: {err}");
+            format!("Your issue was saved as {id:x}, but couldn't be posted for review. Please let the moderators know")
+        },
+    };
+
+    submission.edit_response(ctx, EditInteractionResponse::new().content(content)).await?;
 
     Ok(())
 }
 
+/// Suggests entries from the loadout (or stage-wide data) of the class and stage picked in the command so far
+async fn autocomplete_incorrect(ctx: Context<'_>, partial: &str) -> impl Iterator<Item = String> {
+    let poise::Context::Application(app_ctx) = ctx else { return Vec::new().into_iter() };
+    let option = |name: &str| app_ctx.interaction.data.options.iter()
+        .find(|option| option.name == name)
+        .and_then(|option| option.value.as_i64())
+        .and_then(|index| usize::try_from(index).ok());
+    let Some(stage) = option("stage").and_then(Stage::from_index) else { return Vec::new().into_iter() };
+    let class = option("class").and_then(CalamityClass::from_index);
+    let stage_wide = app_ctx.interaction.data.options.iter()
+        .find(|option| option.name == "stage_wide")
+        .and_then(|option| option.value.as_bool())
+        .unwrap_or_default();
+
+    let partial = partial.to_lowercase();
+    let mut seen = HashSet::new();
+    let loadouts = ctx.data().loadouts.read().await;
+    let Some(stage_data) = loadouts.get_stage(stage) else { return Vec::new().into_iter() };
+    let entries: Vec<String> = match (class, stage_wide) {
+        (Some(class), false) => {
+            let Some(loadout) = stage_data.loadouts.get(&class) else { return Vec::new().into_iter() };
+            loadout.entries().into_iter().map(|(_, entry)| entry.to_string()).collect()
+        },
+        (None, true) => stage_data.entries().into_iter().map(|(_, entry)| entry).collect(),
+        _ => return Vec::new().into_iter(),
+    };
+    entries.into_iter()
+        // autocomplete choices can only be 100 characters long
+        .filter(|entry| entry.to_lowercase().contains(&partial) && entry.chars().count() <= 100)
+        .filter(|entry| seen.insert(entry.clone()))
+        .take(25)
+        .collect::<Vec<_>>()
+        .into_iter()
+}
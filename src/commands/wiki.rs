@@ -3,6 +3,7 @@ use std::time::Duration;
 use poise::{command, serenity_prelude::{ActionRowComponent, ButtonKind, ButtonStyle, Color, CreateActionRow, CreateButton, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, EditInteractionResponse, Timestamp}, ChoiceParameter, CreateReply};
 use reqwest::{header::{HeaderMap, HeaderValue}, redirect::Policy, Client, IntoUrl, Url};
 use scraper::{ElementRef, Html, Node, Selector};
+use serde::Deserialize;
 
 use crate::{Context, PoiseResult};
 
@@ -85,6 +86,43 @@ impl WikiType {
     }
 }
 
+#[derive(Deserialize)]
+struct CargoResponse {
+    cargoquery: Vec<CargoRow>,
+}
+
+#[derive(Deserialize)]
+struct CargoRow {
+    title: CargoItem,
+}
+
+#[derive(Deserialize)]
+struct CargoItem {
+    name: String,
+}
+
+/// Looks up the names of vanilla items by their IDs in the Terraria Wiki's item database
+pub async fn vanilla_item_names(ids: &[i32]) -> Result<Vec<String>, crate::Error> {
+    if ids.is_empty() { return Ok(Vec::new()) }
+
+    let ids = ids.iter().map(i32::to_string).collect::<Vec<_>>().join(",");
+    let url = Url::parse_with_params(WikiType::Vanilla.url().join("/api.php")?.as_str(), &[
+        ("action", "cargoquery"),
+        ("format", "json"),
+        ("tables", "Items"),
+        ("fields", "name"),
+        ("where", &format!("itemid IN ({ids})")),
+        ("limit", "500"),
+    ])?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert("User-Agent", HeaderValue::from_static("Mozilla/5.0 (Macintosh; Intel Mac OS X 10.9; rv:50.0) Gecko/20100101 Firefox/50.0"));
+    let client = reqwest::Client::builder().default_headers(headers).build()?;
+
+    let response: CargoResponse = serde_json::from_str(&client.get(url).send().await?.text().await?)?;
+    Ok(response.cargoquery.into_iter().map(|row| row.title.name).collect())
+}
+
 #[command(
     slash_command,
     description_localized("en-US", "Views a specific item"),
@@ -1,129 +1,620 @@
-use std::collections::HashMap;
+use std::{collections::{HashMap, HashSet}, sync::Arc};
 
+use multimap::MultiMap;
+use num_derive::FromPrimitive;
 use num_traits::{ToPrimitive, FromPrimitive};
-use poise::{serenity_prelude::{Http, UserId, User, ButtonStyle, Color, CreateEmbed, Timestamp, CreateActionRow, CreateButton, CreateEmbedFooter}, ChoiceParameter};
+use poise::{serenity_prelude::{CacheHttp, ChannelId, GuildId, MessageId, RoleId, UserId, User, ButtonStyle, Color, CreateEmbed, Timestamp, CreateActionRow, CreateButton, CreateEmbedFooter}, ChoiceParameter};
 use rand::Rng;
 use sqlx::{PgPool, types::{BigDecimal, chrono::{NaiveDateTime, Utc}}};
-use tracing::info;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
 
-use crate::loadout_data::{CalamityClass, Stage};
+use crate::{loadout_data::{CalamityClass, Stage}, TimeDelta};
 
-type RawIssue = (i32, BigDecimal, i16, i16, String, String, NaiveDateTime);
+type RawIssue = (
+    i32,
+    BigDecimal,
+    Option<i16>,
+    i16,
+    String,
+    String,
+    NaiveDateTime,
+    i16,
+    Option<String>,
+    Option<String>,
+    Vec<String>,
+    Option<String>,
+    Option<BigDecimal>,
+    Option<BigDecimal>,
+);
+
+type RawStatusChange = (i32, i16, BigDecimal, Option<String>, NaiveDateTime);
+
+type RawReportBlock = (BigDecimal, BigDecimal, Option<String>);
 
 #[derive(Debug)]
 pub struct NoIssueFound(pub i32);
 
+#[derive(Debug)]
+pub enum SetStatusError {
+    NoIssueFound,
+    /// Closed issues keep the status they were closed with
+    AlreadyClosed,
+}
+
+#[derive(Debug)]
+pub enum AddSupporterError {
+    NoIssueFound,
+    AlreadyReported,
+}
+
+/// The user has reported too many issues recently, and can report again at `retry_at`
+#[derive(Debug)]
+pub struct RateLimited {
+    pub retry_at: NaiveDateTime,
+}
+
+/// At most `max` reports can be made within `window`. Adding a +1 to an existing issue counts as a report
+#[derive(Clone, Copy, Debug)]
+pub struct RateLimit {
+    pub max: usize,
+    pub window: TimeDelta,
+}
+
+impl RateLimit {
+    /// Parses a comma separated list of limits written as `{max}/{hours}`, like `3/1,10/24`
+    pub fn parse_list(list: &str) -> Option<Vec<RateLimit>> {
+        list.split(',')
+            .map(str::trim)
+            .filter(|limit| !limit.is_empty())
+            .map(|limit| {
+                let (max, hours) = limit.split_once('/')?;
+                Some(RateLimit { max: max.trim().parse().ok()?, window: TimeDelta::try_hours(hours.trim().parse().ok()?)? })
+            })
+            .collect()
+    }
+}
+
+/// Finds when a user that made `reports` (newest first) can report again, or `None` if no limit has been reached
+fn retry_at(reports: &[NaiveDateTime], limits: &[RateLimit], now: NaiveDateTime) -> Option<NaiveDateTime> {
+    limits.iter()
+        .filter_map(|limit| {
+            let recent: Vec<&NaiveDateTime> = reports.iter().filter(|reported_at| **reported_at > now - limit.window).collect();
+            // once the oldest report that counts towards the limit leaves the window, the user can report again
+            limit.max.checked_sub(1).and_then(|index| recent.get(index)).map(|oldest| **oldest + limit.window)
+        })
+        .max()
+}
+
+pub struct ReportBlock {
+    pub blocked_by: UserId,
+    pub reason: Option<String>,
+}
+
 #[derive(Default)]
 #[non_exhaustive]
 pub struct Issues {
     pub issues: HashMap<i32, Issue>,
+    /// Users that aren't allowed to report issues
+    pub blocked: HashMap<UserId, ReportBlock>,
+    /// The role that lets members review issues in each guild, on top of administrators
+    pub reviewer_roles: HashMap<GuildId, RoleId>,
 }
 
 impl Issues {
-    pub async fn create(&mut self, author: &User, class: CalamityClass, stage: Stage, incorrect: String, correct: String, pool: &PgPool) -> &Issue {
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        &mut self,
+        author: &User,
+        class: Option<CalamityClass>,
+        stage: Stage,
+        section: String,
+        incorrect: String,
+        correct: String,
+        explanation: Option<String>,
+        pool: &PgPool,
+    ) -> &Issue {
         let mut id = rand::thread_rng().gen_range(0..i32::MAX);
         while self.issues.contains_key(&id) { id = rand::thread_rng().gen_range(0..i32::MAX); }
 
         let issue = Issue {
             id,
-            author: author.clone(),
+            author: author.id,
+            author_name: Some(author.display_name().to_string()),
             class,
             stage,
+            section: Some(section),
             incorrect,
             correct,
+            explanation,
+            attachments: Vec::new(),
             created_at: Utc::now().naive_utc(),
+            status: IssueStatus::Open,
+            history: Vec::new(),
+            supporters: Vec::new(),
+            message: None,
         };
 
-        sqlx::query("INSERT INTO issues(id, author, class, stage, incorrect, correct, created_at) VALUES($1, $2, $3, $4, $5, $6, $7)")
+        sqlx::query("INSERT INTO issues(id, author, author_name, class, stage, section, incorrect, correct, explanation, attachments, created_at)
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)")
             .bind(issue.id)
-            .bind(BigDecimal::from_u64(issue.author.id.get()).expect("author is valid big decimal"))
-            .bind(issue.class as i16)
+            .bind(BigDecimal::from_u64(issue.author.get()).expect("author is valid big decimal"))
+            .bind(&issue.author_name)
+            .bind(issue.class.map(|class| class as i16))
             .bind(issue.stage as i16)
+            .bind(&issue.section)
             .bind(&issue.incorrect)
             .bind(&issue.correct)
+            .bind(&issue.explanation)
+            .bind(&issue.attachments)
             .bind(issue.created_at)
             .execute(pool).await.expect("query is correct");
 
-        info!("{} created an issue", issue.author.name);
+        info!("{} created an issue", author.name);
 
         self.issues.insert(id, issue);
         self.issues.get(&id).expect("issue exists")
     }
 
-    pub async fn resolve(&mut self, id: i32, pool: &PgPool) -> Result<Issue, NoIssueFound> {
-        if !self.issues.contains_key(&id) { return Err(NoIssueFound(id)) }
+    /// Moves an issue to a new status, recording who changed it and why
+    pub async fn set_status(
+        &mut self,
+        id: i32,
+        status: IssueStatus,
+        reviewer: UserId,
+        reason: Option<String>,
+        pool: &PgPool,
+    ) -> Result<&Issue, SetStatusError> {
+        let issue = self.issues.get_mut(&id).ok_or(SetStatusError::NoIssueFound)?;
+        if issue.status.is_closed() { return Err(SetStatusError::AlreadyClosed) }
+        let change = StatusChange { status, changed_by: reviewer, reason, changed_at: Utc::now().naive_utc() };
+
+        sqlx::query("UPDATE issues SET status = $1 WHERE id = $2")
+            .bind(status as i16)
+            .bind(id)
+            .execute(pool).await.expect("query is valid");
+
+        sqlx::query("INSERT INTO issue_status_changes(issue_id, status, changed_by, reason, changed_at) VALUES ($1, $2, $3, $4, $5)")
+            .bind(id)
+            .bind(status as i16)
+            .bind(BigDecimal::from(reviewer.get()))
+            .bind(&change.reason)
+            .bind(change.changed_at)
+            .execute(pool).await.expect("query is valid");
+
+        info!("issue {:x} was marked as {}", id, status.name());
+
+        issue.status = status;
+        issue.history.push(change);
+        Ok(issue)
+    }
+
+    /// Makes `message` the issue's review message, returning the previous one so its buttons can be removed
+    pub async fn set_message(
+        &mut self,
+        id: i32,
+        channel: ChannelId,
+        message: MessageId,
+        pool: &PgPool,
+    ) -> Result<Option<(ChannelId, MessageId)>, NoIssueFound> {
+        let issue = self.issues.get_mut(&id).ok_or(NoIssueFound(id))?;
 
-        sqlx::query("DELETE FROM issues WHERE id = $1")
+        sqlx::query("UPDATE issues SET channel_id = $1, message_id = $2 WHERE id = $3")
+            .bind(BigDecimal::from(channel.get()))
+            .bind(BigDecimal::from(message.get()))
             .bind(id)
             .execute(pool).await.expect("query is valid");
 
-        self.issues.remove(&id).ok_or(NoIssueFound(id))
+        Ok(issue.message.replace((channel, message)))
     }
 
-    pub async fn load(http: &Http, pool: &PgPool) -> Self {
+    /// Stores the file names of the issue's screenshots
+    pub async fn set_attachments(&mut self, id: i32, attachments: Vec<String>, pool: &PgPool) -> Result<(), NoIssueFound> {
+        let issue = self.issues.get_mut(&id).ok_or(NoIssueFound(id))?;
+
+        sqlx::query("UPDATE issues SET attachments = $1 WHERE id = $2")
+            .bind(&attachments)
+            .bind(id)
+            .execute(pool).await.expect("query is valid");
+
+        issue.attachments = attachments;
+        Ok(())
+    }
+
+    /// Finds an issue that is still being worked on which reports the same phrase in the same loadout
+    pub fn find_duplicate(&self, class: Option<CalamityClass>, stage: Stage, incorrect: &str) -> Option<i32> {
+        self.issues.values()
+            .filter(|issue| !issue.status.is_closed() && issue.class == class && issue.stage == stage)
+            .find(|issue| issue.incorrect.trim().eq_ignore_ascii_case(incorrect.trim()))
+            .map(|issue| issue.id)
+    }
+
+    /// Adds `user` as a +1 on an issue they reported again
+    pub async fn add_supporter(&mut self, id: i32, user: UserId, pool: &PgPool) -> Result<&Issue, AddSupporterError> {
+        let issue = self.issues.get_mut(&id).ok_or(AddSupporterError::NoIssueFound)?;
+        if issue.author == user || issue.supporters.contains(&user) { return Err(AddSupporterError::AlreadyReported) }
+
+        sqlx::query("INSERT INTO issue_supporters(issue_id, user_id, added_at) VALUES ($1, $2, $3)")
+            .bind(id)
+            .bind(BigDecimal::from(user.get()))
+            .bind(Utc::now().naive_utc())
+            .execute(pool).await.expect("query is valid");
+
+        info!("{} added a +1 to issue {:x}", user, id);
+
+        issue.supporters.push(user);
+        Ok(issue)
+    }
+
+    /// Checks every limit against the reports `user` made recently, which are counted from the database
+    pub async fn check_rate_limit(&self, user: UserId, limits: &[RateLimit], pool: &PgPool) -> Result<(), RateLimited> {
+        let Some(longest) = limits.iter().map(|limit| limit.window).max() else { return Ok(()) };
+        let now = Utc::now().naive_utc();
+
+        let reports: Vec<(NaiveDateTime,)> = sqlx::query_as("SELECT created_at FROM issues WHERE author = $1 AND created_at > $2
+                UNION ALL SELECT added_at FROM issue_supporters WHERE user_id = $1 AND added_at > $2
+                ORDER BY 1 DESC")
+            .bind(BigDecimal::from(user.get()))
+            .bind(now - longest)
+            .fetch_all(pool).await.expect("query is valid");
+
+        let reports: Vec<NaiveDateTime> = reports.into_iter().map(|(reported_at,)| reported_at).collect();
+        match retry_at(&reports, limits, now) {
+            Some(retry_at) => Err(RateLimited { retry_at }),
+            None => Ok(()),
+        }
+    }
+
+    /// Stops `user` from reporting issues. Returns `false` if they were already blocked
+    pub async fn block(&mut self, user: UserId, blocked_by: UserId, reason: Option<String>, pool: &PgPool) -> bool {
+        if self.blocked.contains_key(&user) { return false }
+
+        sqlx::query("INSERT INTO report_blocks(user_id, blocked_by, reason) VALUES ($1, $2, $3)")
+            .bind(BigDecimal::from(user.get()))
+            .bind(BigDecimal::from(blocked_by.get()))
+            .bind(&reason)
+            .execute(pool).await.expect("query is valid");
+
+        info!("{} was blocked from reporting issues", user);
+
+        self.blocked.insert(user, ReportBlock { blocked_by, reason });
+        true
+    }
+
+    /// Lets `user` report issues again. Returns `false` if they weren't blocked
+    pub async fn unblock(&mut self, user: UserId, pool: &PgPool) -> bool {
+        if self.blocked.remove(&user).is_none() { return false }
+
+        sqlx::query("DELETE FROM report_blocks WHERE user_id = $1")
+            .bind(BigDecimal::from(user.get()))
+            .execute(pool).await.expect("query is valid");
+
+        info!("{} was unblocked from reporting issues", user);
+
+        true
+    }
+
+    /// Sets the role that lets members review issues in `guild`, or removes it
+    pub async fn set_reviewer_role(&mut self, guild: GuildId, role: Option<RoleId>, pool: &PgPool) {
+        let guild_id = BigDecimal::from(guild.get());
+        match role {
+            Some(role) => {
+                sqlx::query("INSERT INTO issue_reviewer_roles(guild_id, role_id) VALUES ($1, $2)
+                        ON CONFLICT (guild_id) DO UPDATE SET role_id = EXCLUDED.role_id")
+                    .bind(guild_id)
+                    .bind(BigDecimal::from(role.get()))
+                    .execute(pool).await.expect("query is valid");
+                self.reviewer_roles.insert(guild, role);
+            },
+            None => {
+                sqlx::query("DELETE FROM issue_reviewer_roles WHERE guild_id = $1")
+                    .bind(guild_id)
+                    .execute(pool).await.expect("query is valid");
+                self.reviewer_roles.remove(&guild);
+            },
+        }
+    }
+
+    /// Only reads from the database, so authors of issues reported before names were stored have no name until
+    /// [`Issues::fill_author_names`] finds them
+    pub async fn load(pool: &PgPool) -> Self {
         let mut issues = HashMap::new();
 
-        let issue_array: Vec<RawIssue> = sqlx::query_as("SELECT * FROM issues")
+        let issue_array: Vec<RawIssue> = sqlx::query_as("SELECT id, author, class, stage, incorrect, correct, created_at, status, section, explanation, attachments,
+                author_name, channel_id, message_id FROM issues")
+            .fetch_all(pool).await.expect("query is correct");
+
+        let changes: Vec<RawStatusChange> = sqlx::query_as("SELECT issue_id, status, changed_by, reason, changed_at FROM issue_status_changes
+                ORDER BY changed_at")
             .fetch_all(pool).await.expect("query is correct");
+        let mut history: MultiMap<i32, StatusChange> = changes.into_iter()
+            .map(|(issue_id, status, changed_by, reason, changed_at)| (issue_id, StatusChange {
+                status: IssueStatus::from_i16(status).expect("status is valid"),
+                changed_by: UserId::new(changed_by.to_u64().expect("user snowflake is a valid u64")),
+                reason,
+                changed_at,
+            }))
+            .collect();
+
+        let supporters: Vec<(i32, BigDecimal)> = sqlx::query_as("SELECT issue_id, user_id FROM issue_supporters ORDER BY added_at")
+            .fetch_all(pool).await.expect("query is correct");
+        let mut supporters: MultiMap<i32, UserId> = supporters.into_iter()
+            .map(|(issue_id, user_id)| (issue_id, UserId::new(user_id.to_u64().expect("user snowflake is a valid u64"))))
+            .collect();
+
+        let blocks: Vec<RawReportBlock> = sqlx::query_as("SELECT user_id, blocked_by, reason FROM report_blocks")
+            .fetch_all(pool).await.expect("query is correct");
+        let blocked = blocks.into_iter()
+            .map(|(user_id, blocked_by, reason)| (
+                UserId::new(user_id.to_u64().expect("user snowflake is a valid u64")),
+                ReportBlock { blocked_by: UserId::new(blocked_by.to_u64().expect("user snowflake is a valid u64")), reason },
+            ))
+            .collect();
+
+        let reviewer_roles: Vec<(BigDecimal, BigDecimal)> = sqlx::query_as("SELECT guild_id, role_id FROM issue_reviewer_roles")
+            .fetch_all(pool).await.expect("query is correct");
+        let reviewer_roles = reviewer_roles.into_iter()
+            .map(|(guild_id, role_id)| (
+                GuildId::new(guild_id.to_u64().expect("guild snowflake is a valid u64")),
+                RoleId::new(role_id.to_u64().expect("role snowflake is a valid u64")),
+            ))
+            .collect();
 
         for raw_issue in issue_array {
-            let author = raw_issue.1.to_u64().map(|id| UserId::new(id).to_user(http)).expect("id is u64").await.expect("author is user");
-            let class = FromPrimitive::from_i16(raw_issue.2).expect("class number is valid class");
+            let author = UserId::new(raw_issue.1.to_u64().expect("author snowflake is a valid u64"));
+            let class = raw_issue.2.map(|class| FromPrimitive::from_i16(class).expect("class number is valid class"));
             let stage = FromPrimitive::from_i16(raw_issue.3).expect("stage number is valid stage");
             let issue = Issue {
                 id: raw_issue.0,
                 author,
+                author_name: raw_issue.11,
                 class,
                 stage,
+                section: raw_issue.8,
                 incorrect: raw_issue.4,
                 correct: raw_issue.5,
-                created_at: Utc::now().naive_utc(),
+                explanation: raw_issue.9,
+                attachments: raw_issue.10,
+                created_at: raw_issue.6,
+                status: IssueStatus::from_i16(raw_issue.7).expect("status is valid"),
+                history: history.remove(&raw_issue.0).unwrap_or_default(),
+                supporters: supporters.remove(&raw_issue.0).unwrap_or_default(),
+                message: raw_issue.12.zip(raw_issue.13).map(|(channel_id, message_id)| (
+                    ChannelId::new(channel_id.to_u64().expect("channel snowflake is a valid u64")),
+                    MessageId::new(message_id.to_u64().expect("message snowflake is a valid u64")),
+                )),
             };
             issues.insert(raw_issue.0, issue);
         }
 
         Issues {
             issues,
+            blocked,
+            reviewer_roles,
+        }
+    }
+
+    /// Looks up the authors of issues that have no author name and stores their names. Each author is looked up once, through the
+    /// cache when possible, so this can run in the background instead of holding up startup
+    pub async fn fill_author_names(cache_http: impl CacheHttp, issues: Arc<RwLock<Issues>>, pool: PgPool) {
+        let authors: HashSet<UserId> = issues.read().await.issues.values()
+            .filter(|issue| issue.author_name.is_none())
+            .map(|issue| issue.author)
+            .collect();
+        if authors.is_empty() { return }
+
+        let mut names = HashMap::new();
+        for author in authors {
+            match author.to_user(&cache_http).await {
+                Ok(user) => { names.insert(author, user.display_name().to_string()); },
+                Err(err) => warn!("could not find issue author {author}: {err}"),
+            }
+        }
+
+        // the names are stored under a single lock, so a sync can't swap the issues out between the database and memory updates
+        let mut issues = issues.write().await;
+        for (author, name) in &names {
+            sqlx::query("UPDATE issues SET author_name = $1 WHERE author = $2 AND author_name IS NULL")
+                .bind(name)
+                .bind(BigDecimal::from(author.get()))
+                .execute(&pool).await.expect("query is valid");
+
+            issues.issues.values_mut()
+                .filter(|issue| issue.author == *author && issue.author_name.is_none())
+                .for_each(|issue| issue.author_name = Some(name.clone()));
         }
+
+        info!("filled in the names of {} issue authors", names.len());
     }
 }
 
 pub struct Issue {
     pub id: i32,
-    pub author: User,
-    pub class: CalamityClass,
+    pub author: UserId,
+    /// The author's display name when they reported the issue. Issues reported before names were stored have none until
+    /// [`Issues::fill_author_names`] runs
+    pub author_name: Option<String>,
+    /// The class whose loadout the issue is in. Issues with stage-wide data, like the healing potion and powerups, have none
+    pub class: Option<CalamityClass>,
     pub stage: Stage,
+    /// The part of the loadout the issue is in, which is either a header or the label of extra data. Issues reported before sections
+    /// were recorded have none
+    pub section: Option<String>,
     pub incorrect: String,
     pub correct: String,
+    /// Why the reporter thinks the phrase is wrong
+    pub explanation: Option<String>,
+    /// The file names of the report's screenshots, which are attached to the first message the issue was posted in. Discord's links
+    /// to attachments expire, so they aren't kept
+    pub attachments: Vec<String>,
     pub created_at: NaiveDateTime,
+    pub status: IssueStatus,
+    /// Every status the issue has been moved to, oldest first
+    pub history: Vec<StatusChange>,
+    /// Users that reported the same issue after the author, oldest first
+    pub supporters: Vec<UserId>,
+    /// The message with the issue's review buttons. Issues reported before messages were stored have none
+    pub message: Option<(ChannelId, MessageId)>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ChoiceParameter, FromPrimitive)]
+pub enum IssueStatus {
+    #[default]
+    Open,
+    Accepted,
+    Rejected,
+    Duplicate,
+    Resolved,
+}
+
+impl IssueStatus {
+    /// Closed issues need no more work, but are kept as a record of the report
+    pub fn is_closed(self) -> bool {
+        matches!(self, Self::Rejected | Self::Duplicate | Self::Resolved)
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Self::Open => Color::ORANGE,
+            Self::Accepted => Color::BLUE,
+            Self::Rejected => Color::RED,
+            Self::Duplicate => Color::DARK_GREY,
+            Self::Resolved => Color::from_rgb(21, 209, 49),
+        }
+    }
+}
+
+pub struct StatusChange {
+    pub status: IssueStatus,
+    pub changed_by: UserId,
+    pub reason: Option<String>,
+    pub changed_at: NaiveDateTime,
 }
 
 impl Issue {
     pub fn create_embed(&self) -> CreateEmbed {
-        CreateEmbed::default()
-            .title(format!("Issue {:x}", self.id))
-            .field("Class", self.class.name(), true)
+        let title = match self.status {
+            IssueStatus::Open => format!("Issue {:x}", self.id),
+            status => format!("{} {:x}", status.name(), self.id),
+        };
+        let mut embed = CreateEmbed::default()
+            .title(title)
+            .field("Class", self.class_name(), true)
             .field("Stage", self.stage.name(), true)
+            .field("Section", self.section.as_deref().unwrap_or("Unknown"), true)
             .field("** **", "** **", false)
             .field("Incorrect Phrase", &self.incorrect, true)
-            .field("Correct Phrase", &self.correct, true)
-            .color(Color::ORANGE)
-            .footer(CreateEmbedFooter::new(format!("Created by {}", self.author.name)))
+            .field("Correct Phrase", &self.correct, true);
+        if let Some(explanation) = &self.explanation {
+            embed = embed.field("Explanation", explanation, false);
+        }
+        if !self.attachments.is_empty() {
+            let names = self.attachments.iter().map(|name| format!("`{name}`")).collect::<Vec<_>>();
+            embed = embed.field("Screenshots", format!("{}\nAttached to the issue's first message", names.join(", ")), false);
+        }
+        if !self.supporters.is_empty() {
+            let supporters = self.supporters.iter().map(|user| format!("<@{user}>")).collect::<Vec<_>>();
+            embed = embed.field(format!("+{}", supporters.len()), supporters.join(" "), false);
+        }
+        if let Some(change) = self.history.last() {
+            let reason = change.reason.as_ref().map(|reason| format!("\n> {reason}")).unwrap_or_default();
+            embed = embed.field("Status", format!("{} by <@{}>{reason}", change.status.name(), change.changed_by), false);
+        }
+
+        embed
+            .color(self.status.color())
+            .footer(CreateEmbedFooter::new(format!("Created by {}", self.author_name.as_deref().unwrap_or("Unknown"))))
             .timestamp(Timestamp::from_unix_timestamp(self.created_at.and_utc().timestamp()).expect("timestamp is valid"))
     }
 
-    pub fn create_components(&self) -> Vec<CreateActionRow> {
-        vec![
-            CreateActionRow::Buttons(vec![
-                CreateButton::new(format!("r-{}", self.id)).style(ButtonStyle::Success).label("Resolve"),
-            ]),
-        ]
+    pub fn class_name(&self) -> &'static str {
+        self.class.map_or("Stage-wide", |class| class.name())
     }
 
-    pub fn create_resolved_embed(&self) -> CreateEmbed {
-        self.create_embed()
-            .title(format!("Resolved {:x}", self.id))
-            .color(Color::from_rgb(21, 209, 49))
+    /// The message sent to the issue's author when its status changes
+    pub fn create_update_embed(&self) -> CreateEmbed {
+        let description = match self.status {
+            IssueStatus::Accepted => "Your report was accepted and will be fixed soon",
+            IssueStatus::Rejected => "Your report was rejected",
+            IssueStatus::Duplicate => "Your report was marked as a duplicate of another report",
+            IssueStatus::Resolved => "Your report was resolved. Thanks for helping improve the loadouts!",
+            // issues can't be moved back to open, so this only describes where a new report stands
+            IssueStatus::Open => "Your report is waiting to be reviewed",
+        };
+        let mut embed = CreateEmbed::new()
+            .title(format!("Issue {:x} {}", self.id, self.status.name()))
+            .description(description)
+            .field("Loadout", format!("{} - {}", self.class_name(), self.stage.name()), false)
+            .field("Incorrect Phrase", &self.incorrect, true)
+            .field("Correct Phrase", &self.correct, true);
+        if let Some(reason) = self.history.last().and_then(|change| change.reason.as_ref()) {
+            embed = embed.field("Reviewer's Note", reason, false);
+        }
+        if let Some(class) = self.class {
+            embed = embed.url(crate::get_loadout_url(class, self.stage));
+        }
+
+        embed
+            .color(self.status.color())
+            .footer(CreateEmbedFooter::new("Turn these messages off with /preferences issue-updates"))
+            .timestamp(Timestamp::now())
+    }
+
+    /// The buttons reviewers use to move the issue along. Closed issues have none
+    pub fn create_components(&self) -> Vec<CreateActionRow> {
+        if self.status.is_closed() { return Vec::new() }
+
+        let mut buttons = Vec::new();
+        if self.status == IssueStatus::Open {
+            buttons.push(CreateButton::new(format!("issue-accept-{}", self.id)).style(ButtonStyle::Primary).label("Accept"));
+        }
+        buttons.extend([
+            CreateButton::new(format!("issue-reject-{}", self.id)).style(ButtonStyle::Danger).label("Reject"),
+            CreateButton::new(format!("issue-duplicate-{}", self.id)).style(ButtonStyle::Secondary).label("Mark Duplicate"),
+            CreateButton::new(format!("issue-resolve-{}", self.id)).style(ButtonStyle::Success).label("Resolve"),
+            CreateButton::new(format!("issue-apply-{}", self.id)).style(ButtonStyle::Success).label("Apply Fix"),
+        ]);
+
+        vec![CreateActionRow::Buttons(buttons)]
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use sqlx::types::chrono::{NaiveDate, NaiveDateTime};
+
+    use super::{retry_at, RateLimit};
+    use crate::TimeDelta;
+
+    fn at(hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|date| date.and_hms_opt(hour, minute, 0)).expect("time is valid")
+    }
+
+    #[test]
+    fn parses_rate_limits() {
+        let limits = RateLimit::parse_list(" 3/1, 10/24,").expect("limits parse");
+        assert_eq!(limits.len(), 2);
+        assert_eq!((limits[0].max, limits[0].window), (3, TimeDelta::try_hours(1).expect("hours are valid")));
+        assert_eq!((limits[1].max, limits[1].window), (10, TimeDelta::try_hours(24).expect("hours are valid")));
+
+        assert!(RateLimit::parse_list("").expect("empty list parses").is_empty());
+        assert!(RateLimit::parse_list("3").is_none());
+        assert!(RateLimit::parse_list("3/x").is_none());
+        assert!(RateLimit::parse_list("-1/1").is_none());
+    }
+
+    #[test]
+    fn finds_when_users_can_report_again() {
+        let hourly = RateLimit { max: 2, window: TimeDelta::try_hours(1).expect("hours are valid") };
+        let daily = RateLimit { max: 3, window: TimeDelta::try_hours(24).expect("hours are valid") };
+        let now = at(12, 0);
+
+        assert_eq!(retry_at(&[at(11, 50)], &[hourly], now), None);
+        // the older of the two reports in the last hour has to leave the window
+        assert_eq!(retry_at(&[at(11, 50), at(11, 30), at(9, 0)], &[hourly], now), Some(at(12, 30)));
+        // the latest time wins when several limits are reached
+        assert_eq!(retry_at(&[at(11, 50), at(11, 30), at(9, 0)], &[hourly, daily], now), Some(at(9, 0) + daily.window));
+        assert_eq!(retry_at(&[at(11, 50), at(11, 30)], &[RateLimit { max: 0, ..hourly }], now), None);
+        assert_eq!(retry_at(&[], &[], now), None);
+    }
+}
@@ -133,6 +133,30 @@ impl LoadoutData {
         Ok(())
     }
 
+    pub async fn set_potion(&mut self, pool: &PgPool, stage: Stage, potion: PotionType) -> Result<(), StageNotFoundError> {
+        let stage_data = self.loadouts.get_mut(&stage).ok_or(StageNotFoundError(stage))?;
+        stage_data.potion = potion;
+
+        sqlx::query("UPDATE stage_data SET health_potion = $1 WHERE stage = $2")
+            .bind(potion)
+            .bind(stage as i16)
+            .execute(pool).await.expect("query is valid");
+
+        Ok(())
+    }
+
+    pub async fn set_powerups(&mut self, pool: &PgPool, stage: Stage, powerups: Vec<Powerup>) -> Result<(), StageNotFoundError> {
+        let stage_data = self.loadouts.get_mut(&stage).ok_or(StageNotFoundError(stage))?;
+        stage_data.powerups = Some(powerups);
+
+        sqlx::query("UPDATE stage_data SET powerups = $1 WHERE stage = $2")
+            .bind(stage_data.powerups.as_deref())
+            .bind(stage as i16)
+            .execute(pool).await.expect("query is valid");
+
+        Ok(())
+    }
+
     pub async fn reset(pool: &PgPool) {
         sqlx::query("TRUNCATE stage_data, extra_loadout_data, loadouts RESTART IDENTITY CASCADE")
             .execute(pool).await.expect("valid query");
@@ -281,6 +305,86 @@ impl StageData {
         embed
     }
 
+    /// The data shared by every class in this stage, along with the section it's shown in
+    pub fn entries(&self) -> Vec<(&'static str, String)> {
+        iter::once(("Healing Potion", self.potion.to_string()))
+            .chain(self.powerups.iter().flatten().map(|powerup| ("Permanent Powerups", powerup.to_string())))
+            .collect()
+    }
+
+    /// The entry that looks the most like `phrase`, like [`Loadout::suggest`]
+    pub fn suggest(&self, phrase: &str) -> Option<String> {
+        let entries = self.entries();
+        closest_entry(entries.iter().map(|(_, entry)| &entry[..]), phrase).map(str::to_string)
+    }
+
+    /// Finds the healing potion or powerup containing `incorrect` and returns the change that replaces it with `correct`. Since this data
+    /// can only be a known potion or powerup, there is no fix if the corrected entry isn't one
+    pub fn find_fix(&self, incorrect: &str, correct: &str) -> Option<LoadoutFix> {
+        if incorrect.is_empty() { return None }
+
+        let potion = self.potion.to_string();
+        if let Some(after) = replace_ignore_case(&potion, incorrect, correct) {
+            let new_potion = parse_named(&PotionType::ALL, &after)?;
+            return Some(LoadoutFix { section: str!("Healing Potion"), before: potion, after, change: LoadoutChange::Potion(new_potion) });
+        }
+
+        let powerups = self.powerups.as_ref()?;
+        let (i, before, after) = powerups.iter().map(Powerup::to_string).enumerate()
+            .find_map(|(i, powerup)| Some((i, powerup.clone(), replace_ignore_case(&powerup, incorrect, correct)?)))?;
+        let mut new_powerups = powerups.clone();
+        new_powerups[i] = parse_named(&Powerup::ALL, &after)?;
+        Some(LoadoutFix { section: str!("Permanent Powerups"), before, after, change: LoadoutChange::Powerups(new_powerups) })
+    }
+
+    /// Items from this stage's loadouts that help the whole party rather than just the player using them: summoner whips and sentries,
+    /// along with support weapons (written in italics)
+    pub fn support_items(&self) -> Vec<SupportItem<'_>> {
+        let mut items = Vec::new();
+        for class in CalamityClass::all() {
+            let Some(loadout) = self.loadouts.get(&class) else { continue };
+            items.extend(loadout.extra.iter()
+                .filter(|(label, _)| SUPPORT_LABELS.contains(&label.trim_end_matches('*')))
+                .flat_map(|(label, values)| values.iter().map(move |item| SupportItem { class, kind: label.trim_end_matches('*'), item: strip_markdown(item) })));
+            items.extend(loadout.weapons.iter()
+                .filter(|weapon| weapon.starts_with('*') && !weapon.starts_with("**"))
+                .map(|weapon| SupportItem { class, kind: "Support Weapon", item: strip_markdown(weapon) }));
+        }
+        items
+    }
+
+    /// The main recommended weapon (written in bold) of a class's loadout
+    pub fn main_weapon(&self, class: CalamityClass) -> Option<String> {
+        self.loadouts.get(&class)?.weapons.iter()
+            .find(|weapon| weapon.starts_with("**"))
+            .map(|weapon| strip_markdown(weapon))
+    }
+
+    /// A plain text version of [`StageData::create_embed`], for players who prefer compact messages
+    pub fn create_text(&self, class: CalamityClass, stage: Stage) -> String {
+        let loadout = self.loadouts.get(&class).expect("loadout exists for stage");
+        let mut lines = vec![
+            format!("**{} - {}** (<{}>)", class.name(), stage.name(), crate::get_loadout_url(class, stage)),
+            format!("Armor: {}", loadout.armor),
+            format!("Weapons: {}", loadout.weapons.join(", ")),
+            format!("Equipment: {}", loadout.equipment.join(", ")),
+        ];
+        lines.extend(loadout.extra.iter().map(|(title, list)| format!("{title}: {}", list.join(", "))));
+        lines.push(format!("Healing Potion: {}", self.potion));
+        if let Some(powerups) = &self.powerups {
+            lines.push(format!("Permanent Powerups: {}", powerups.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Extra loadout sections that hold items benefiting the whole party
+const SUPPORT_LABELS: [&str; 2] = ["Whip", "Sentry"];
+
+pub struct SupportItem<'a> {
+    pub class: CalamityClass,
+    pub kind: &'a str,
+    pub item: String,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug, sqlx::Type)]
@@ -311,6 +415,30 @@ pub enum Powerup {
     CelestialOnion,
 }
 
+impl Powerup {
+    pub const ALL: [Powerup; 19] = [
+        Self::LifeCrystal,
+        Self::LifeFruit,
+        Self::BloodOrange,
+        Self::MiracleFruit,
+        Self::Elderberry,
+        Self::Dragonfruit,
+        Self::ManaCrystal,
+        Self::CometShard,
+        Self::EtherealCore,
+        Self::PhantomHeart,
+        Self::MushroomPlasmaRoot,
+        Self::InfernalBlood,
+        Self::RedLightningContainer,
+        Self::ElectrolyteGelPack,
+        Self::StarlightFuelCell,
+        Self::Ectoheart,
+        Self::HermitBox,
+        Self::DemonHeart,
+        Self::CelestialOnion,
+    ];
+}
+
 impl Display for Powerup {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let str = match self {
@@ -335,6 +463,10 @@ pub enum PotionType {
     Omega,
 }
 
+impl PotionType {
+    pub const ALL: [PotionType; 6] = [Self::Lesser, Self::Normal, Self::Greater, Self::Super, Self::Supreme, Self::Omega];
+}
+
 impl Display for PotionType {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         Debug::fmt(&self, f)
@@ -380,6 +512,7 @@ impl Display for Stage {
     }
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for Stage {
     fn default() -> Self {
         Self::PreBoss
@@ -387,6 +520,10 @@ impl Default for Stage {
 }
 
 impl Stage {
+    pub fn all() -> impl Iterator<Item = Stage> {
+        (0_usize..).map_while(FromPrimitive::from_usize)
+    }
+
     pub fn img(&self) -> Url {
         use Stage::*;
 
@@ -426,6 +563,19 @@ impl Display for CalamityClass {
 }
 
 impl CalamityClass {
+    pub fn all() -> impl Iterator<Item = CalamityClass> {
+        (0_usize..).map_while(FromPrimitive::from_usize)
+    }
+
+    /// Parses a comma separated list of class names, ignoring case
+    pub fn parse_list(list: &str) -> Option<Vec<CalamityClass>> {
+        list.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| Self::all().find(|class| class.name().eq_ignore_ascii_case(name)))
+            .collect()
+    }
+
     pub fn emoji(&self) -> String {
         match self {
             Self::Melee => str!("<:melee:1312528694367092780>"),
@@ -446,6 +596,173 @@ pub struct Loadout {
     pub extra: LinkedHashMap<String, Vec<String>>,
 }
 
+impl Loadout {
+    /// Checks which armor, weapons and equipment of this loadout are in `items`. Entries with alternatives (separated by `/`) are
+    /// owned if any of the alternatives are owned, and armor is owned if any piece of the set is owned
+    pub fn check(&self, items: &[String]) -> Vec<LoadoutCheck<'_>> {
+        let items: Vec<String> = items.iter().map(|item| normalize_item(item)).collect();
+        let owns = |entry: &str, is_armor: bool| {
+            entry.split('/')
+                .map(normalize_item)
+                .filter(|alternative| !alternative.is_empty())
+                .any(|alternative| items.iter().any(|item| if is_armor { item.starts_with(&alternative) } else { *item == alternative }))
+        };
+
+        vec![
+            LoadoutCheck { section: "Armor", entries: vec![(&self.armor, owns(&self.armor, true))] },
+            LoadoutCheck { section: "Weapons", entries: self.weapons.iter().map(|weapon| (&weapon[..], owns(weapon, false))).collect() },
+            LoadoutCheck { section: "Equipment", entries: self.equipment.iter().map(|equipment| (&equipment[..], owns(equipment, false))).collect() },
+        ]
+    }
+
+    /// Every entry of the loadout along with the section it is in, which is either a header or the label of extra data
+    pub fn entries(&self) -> Vec<(&str, &str)> {
+        iter::once(("Armor", &self.armor[..]))
+            .chain(self.weapons.iter().map(|weapon| ("Weapons", &weapon[..])))
+            .chain(self.equipment.iter().map(|equipment| ("Equipment", &equipment[..])))
+            .chain(self.extra.iter().flat_map(|(label, values)| values.iter().map(move |value| (&label[..], &value[..]))))
+            .collect()
+    }
+
+    /// The entry that looks the most like `phrase`, used to suggest what a player meant when their phrase isn't in the loadout
+    pub fn suggest(&self, phrase: &str) -> Option<&str> {
+        closest_entry(self.entries().into_iter().map(|(_, entry)| entry), phrase)
+    }
+
+    /// Finds the first entry containing `incorrect`, ignoring case (checking armor, weapons, equipment and then extra data) and returns
+    /// the change that replaces it with `correct`
+    pub fn find_fix(&self, incorrect: &str, correct: &str) -> Option<LoadoutFix> {
+        if incorrect.is_empty() { return None }
+        let replace = |entry: &str| replace_ignore_case(entry, incorrect, correct);
+        let replace_in = |entries: &[String]| entries.iter().enumerate().find_map(|(i, entry)| {
+            let fixed = replace(entry)?;
+            let mut entries = entries.to_vec();
+            entries[i] = fixed.clone();
+            Some((entry.clone(), fixed, entries))
+        });
+
+        if let Some(armor) = replace(&self.armor) {
+            return Some(LoadoutFix {
+                section: str!("Armor"),
+                before: self.armor.clone(),
+                after: armor.clone(),
+                change: LoadoutChange::Header(LoadoutHeader::Armor(armor)),
+            });
+        }
+        if let Some((before, after, weapons)) = replace_in(&self.weapons) {
+            let weapons = weapons.try_into().expect("there are 4 weapons");
+            return Some(LoadoutFix { section: str!("Weapons"), before, after, change: LoadoutChange::Header(LoadoutHeader::Weapons(weapons)) });
+        }
+        if let Some((before, after, equipment)) = replace_in(&self.equipment) {
+            return Some(LoadoutFix { section: str!("Equipment"), before, after, change: LoadoutChange::Header(LoadoutHeader::Equipment(equipment)) });
+        }
+        self.extra.iter().find_map(|(label, values)| {
+            let (before, after, values) = replace_in(values)?;
+            Some(LoadoutFix { section: label.clone(), before, after, change: LoadoutChange::Extra(label.clone(), values) })
+        })
+    }
+}
+
+/// A correction to a single entry of a loadout
+pub struct LoadoutFix {
+    /// The part of the loadout the entry is in, which is either a header or the label of extra data
+    pub section: String,
+    pub before: String,
+    pub after: String,
+    pub change: LoadoutChange,
+}
+
+/// The new data for the part of the loadout that a [`LoadoutFix`] changes
+pub enum LoadoutChange {
+    Header(LoadoutHeader),
+    /// An extra label along with its new values
+    Extra(String, Vec<String>),
+    Potion(PotionType),
+    Powerups(Vec<Powerup>),
+}
+
+pub struct LoadoutCheck<'a> {
+    pub section: &'static str,
+    pub entries: Vec<(&'a str, bool)>,
+}
+
+/// The entry that looks the most like `phrase`. Entries with alternatives (separated by `/`) are as close as their closest alternative
+fn closest_entry<'a>(entries: impl Iterator<Item = &'a str>, phrase: &str) -> Option<&'a str> {
+    let phrase = normalize_item(phrase);
+    entries.min_by_key(|entry| entry.split('/').map(|alternative| edit_distance(&normalize_item(alternative), &phrase)).min())
+}
+
+/// Finds the value whose name is `name`, ignoring case and punctuation. Names can leave out the end of the value's name, like
+/// `Life Crystal` for `Life Crystal (15)`
+fn parse_named<T: Display + Copy>(all: &[T], name: &str) -> Option<T> {
+    let name = normalize_item(name);
+    if name.is_empty() { return None }
+    all.iter().find(|value| normalize_item(&value.to_string()) == name)
+        .or_else(|| all.iter().find(|value| normalize_item(&value.to_string()).starts_with(&name)))
+        .copied()
+}
+
+/// The number of single character insertions, deletions and substitutions needed to turn `a` into `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Replaces every occurrence of `from` in `text` with `to`, ignoring case like reported issues do. Returns `None` if there are none
+fn replace_ignore_case(text: &str, from: &str, to: &str) -> Option<String> {
+    if from.is_empty() { return None }
+    // ASCII lowercasing keeps every character the same length, so the indices also point into `text`
+    let lowercase = text.to_ascii_lowercase();
+    let matches: Vec<usize> = lowercase.match_indices(&from.to_ascii_lowercase()).map(|(i, _)| i).collect();
+    if matches.is_empty() { return None }
+
+    let mut replaced = String::new();
+    let mut last = 0;
+    for i in matches {
+        replaced += &text[last..i];
+        replaced += to;
+        last = i + from.len();
+    }
+    replaced += &text[last..];
+    Some(replaced)
+}
+
+/// Removes the bold and italics from each alternative of a loadout entry, such as `*A*/*B*`
+fn strip_markdown(entry: &str) -> String {
+    entry.split('/')
+        .map(|alternative| alternative.trim().trim_matches('*'))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Normalizes an item name so that loadout entries (which may contain markdown) can be compared with item names from other sources
+pub fn normalize_item(name: &str) -> String {
+    name.chars()
+        .filter(|char| char.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// The tModLoader mods the loadouts are made for, along with mods that don't add any items that would change them
+const SUPPORTED_MODS: [&str; 6] = ["CalamityMod", "CalamityModMusic", "BossChecklist", "RecipeBrowser", "MagicStorage", "AutoTrash"];
+
+/// Returns the mods in a tModLoader mod list whose items aren't accounted for in the loadouts
+pub fn unsupported_mods(mods: &[String]) -> Vec<&str> {
+    mods.iter()
+        .map(|name| &name[..])
+        .filter(|name| !SUPPORTED_MODS.contains(name))
+        .collect()
+}
+
 pub enum LoadoutHeader {
     Armor(String),
     Weapons([String; 4]),
@@ -474,6 +791,10 @@ pub enum AddExtraError {
     LabelAlreadyExists(String),
 }
 
+#[derive(Error, Debug)]
+#[error("Stage data not found for stage {0}")]
+pub struct StageNotFoundError(pub Stage);
+
 #[derive(Error, Debug)]
 #[error("Loadout not found with stage {stage} and class {class}")]
 pub struct LoadoutNotFoundError {
@@ -481,3 +802,86 @@ pub struct LoadoutNotFoundError {
     pub class: CalamityClass,
 }
 
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use linked_hash_map::LinkedHashMap;
+
+    use super::{edit_distance, strip_markdown, Loadout, LoadoutChange, LoadoutHeader, PotionType, StageData};
+
+    fn loadout() -> Loadout {
+        let mut extra = LinkedHashMap::new();
+        extra.insert(String::from("Whips*"), vec![String::from("Snapthorn")]);
+        Loadout {
+            id: None,
+            armor: String::from("Wulfrum Armor"),
+            weapons: [
+                String::from("**Acid Gun**/**Space Gun**"),
+                String::from("Demon Scythe"),
+                String::from("*Hardened Honey Comb*"),
+                String::from("Hellwing Staff"),
+            ],
+            equipment: vec![String::from("Hermes Boots"), String::from("Cloud in a Bottle")],
+            extra,
+        }
+    }
+
+    #[test]
+    fn measures_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("minishark", "minishark"), 0);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("abc", ""), 3);
+        assert_eq!(edit_distance("flaw", "lawn"), 2);
+    }
+
+    #[test]
+    fn suggests_closest_entry() {
+        let loadout = loadout();
+        assert_eq!(loadout.suggest("hermes boot"), Some("Hermes Boots"));
+        // entries with alternatives are as close as their closest alternative
+        assert_eq!(loadout.suggest("Spac Gun"), Some("**Acid Gun**/**Space Gun**"));
+        assert_eq!(loadout.suggest("snapthron"), Some("Snapthorn"));
+    }
+
+    #[test]
+    fn finds_fix_in_each_section() {
+        let loadout = loadout();
+
+        let fix = loadout.find_fix("Wulfrum", "Aerospec").expect("armor fix");
+        assert_eq!((&fix.section[..], &fix.after[..]), ("Armor", "Aerospec Armor"));
+        assert!(matches!(fix.change, LoadoutChange::Header(LoadoutHeader::Armor(armor)) if armor == "Aerospec Armor"));
+
+        let fix = loadout.find_fix("Demon Scythe", "Frost Bolt").expect("weapon fix");
+        assert_eq!((&fix.section[..], &fix.before[..], &fix.after[..]), ("Weapons", "Demon Scythe", "Frost Bolt"));
+        assert!(matches!(fix.change, LoadoutChange::Header(LoadoutHeader::Weapons(weapons)) if weapons[1] == "Frost Bolt"));
+
+        let fix = loadout.find_fix("Snapthorn", "Spinal Tap").expect("extra fix");
+        assert_eq!(fix.section, "Whips*");
+        assert!(matches!(fix.change, LoadoutChange::Extra(label, values) if label == "Whips*" && values == ["Spinal Tap"]));
+
+        assert!(loadout.find_fix("Minishark", "Megashark").is_none());
+        assert!(loadout.find_fix("", "Megashark").is_none());
+    }
+
+    #[test]
+    fn finds_fix_ignoring_case() {
+        let fix = loadout().find_fix("hermes boots", "Spectre Boots").expect("equipment fix");
+        assert_eq!((&fix.before[..], &fix.after[..]), ("Hermes Boots", "Spectre Boots"));
+        assert!(matches!(fix.change, LoadoutChange::Header(LoadoutHeader::Equipment(equipment)) if equipment[0] == "Spectre Boots"));
+
+        let stage_data = StageData { potion: PotionType::Lesser, powerups: None, loadouts: HashMap::new() };
+        let fix = stage_data.find_fix("lesser", "Greater").expect("potion fix");
+        assert!(matches!(fix.change, LoadoutChange::Potion(PotionType::Greater)));
+    }
+
+    #[test]
+    fn strips_markdown_from_alternatives() {
+        assert_eq!(strip_markdown("**Acid Gun**/**Space Gun**"), "Acid Gun/Space Gun");
+        assert_eq!(strip_markdown("*A*/*B*/*C*"), "A/B/C");
+        assert_eq!(strip_markdown("Demon Scythe"), "Demon Scythe");
+    }
+}
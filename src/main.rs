@@ -1,6 +1,6 @@
 #![warn(unused_crate_dependencies)]
 use axum::Router;
-use poise::serenity_prelude::{self as serenity, CreateInteractionResponse, CreateInteractionResponseMessage};
+use poise::serenity_prelude as serenity;
 
 use reqwest::Url;
 use tokio::net::TcpListener;
@@ -9,8 +9,8 @@ use tokio::sync::RwLock;
 use std::env;
 use std::{fs, net::SocketAddr, sync::Arc, result::Result};
 
-use commands::{report::report, db::db, loadout::loadout, edit_loadout::edit_loadout};
-use issue::{Issues, NoIssueFound};
+use commands::{report::report, db::db, loadout::loadout, edit_loadout::edit_loadout, lfg::lfg};
+use issue::{Issues, RateLimit};
 use loadout_data::{CalamityClass, LoadoutData, Stage};
 use poise::{
     samples::register_globally,
@@ -22,10 +22,8 @@ use serenity::{
     OnlineStatus,
     GuildId,
     ChannelId,
-    GuildChannel,
     Interaction,
     GatewayIntents,
-    ComponentInteractionDataKind,
     Client,
     FullEvent,
 };
@@ -33,16 +31,27 @@ use serenity::{
 use shuttle_runtime::{CustomError, Service};
 use shuttle_runtime::SecretStore;
 
-use sqlx::{PgPool, Executor};
-use tracing::info;
+use sqlx::{PgPool, Executor, types::chrono::NaiveDateTime};
+use tracing::{error, info};
 
-use crate::{commands::{ping::ping, help::help, playthrough::playthrough, wiki::wiki}, playthrough_data::PlaythroughData};
+use crate::{commands::{ping::ping, help::help, playthrough::playthrough, preferences::preferences, wiki::wiki}, playthrough_data::PlaythroughData, preferences::PreferenceData, scheduler::Scheduler};
 
 mod web;
 mod loadout_data;
 mod playthrough_data;
 mod commands;
 mod issue;
+mod boss;
+mod announce;
+mod nbt;
+mod world_file;
+mod player_file;
+mod scheduler;
+mod preferences;
+mod roles;
+mod recap;
+mod vote;
+mod changelog;
 
 #[macro_export]
 macro_rules! str {
@@ -54,19 +63,24 @@ macro_rules! str {
 pub type Error = Box<dyn std::error::Error + Send + Sync>;
 pub type PoiseResult = Result<(), Error>;
 pub type Context<'a> = poise::Context<'a, Data, Error>;
+/// chrono's duration type, which sqlx doesn't re-export by name
+pub type TimeDelta = <NaiveDateTime as std::ops::Sub>::Output;
 
 pub struct Data {
     pool: PgPool,
-    issue_channel: GuildChannel,
+    issue_channel: ChannelId,
+    report_limits: Vec<RateLimit>,
 
     loadouts: Arc<RwLock<LoadoutData>>,
     playthroughs: Arc<RwLock<PlaythroughData>>,
     issues: Arc<RwLock<Issues>>,
+    preferences: Arc<RwLock<PreferenceData>>,
 }
 
 struct PoiseAxumService {
     pub poise: Client,
     pub axum: Router,
+    pub scheduler: Scheduler,
 }
 
 #[shuttle_runtime::async_trait]
@@ -77,6 +91,7 @@ impl Service for PoiseAxumService {
         tokio::select! {
             _ = self.poise.start() => {},
             _ = web_server => {},
+            _ = self.scheduler.run() => {},
         }
 
         Ok(())
@@ -100,9 +115,21 @@ async fn poise(
     let loadouts = Arc::new(RwLock::new(LoadoutData::default()));
     let playthroughs = Arc::new(RwLock::new(PlaythroughData::default()));
     let issues = Arc::new(RwLock::new(Issues::default()));
+    let user_preferences = Arc::new(RwLock::new(PreferenceData::default()));
 
     let loadouts_setup = loadouts.clone();
     let playthroughs_setup = playthroughs.clone();
+    let playthroughs_scheduler = playthroughs.clone();
+    let issues_web = issues.clone();
+    let preferences_setup = user_preferences.clone();
+    let pool_scheduler = pool.clone();
+    let pool_web = pool.clone();
+
+    let stale_days = secret_store.get("STALE_PLAYTHROUGH_DAYS").and_then(|days| days.parse().ok()).unwrap_or(14);
+    let archive_days = secret_store.get("ARCHIVE_PLAYTHROUGH_DAYS").and_then(|days| days.parse().ok()).unwrap_or(14);
+    let archive_paused_days = secret_store.get("ARCHIVE_PAUSED_DAYS").and_then(|days| days.parse().ok()).unwrap_or(180);
+    let report_limits = secret_store.get("REPORT_RATE_LIMITS").unwrap_or_else(|| str!("3/1,10/24"));
+    let report_limits = RateLimit::parse_list(&report_limits).expect("report rate limits should be valid");
 
     let framework = poise::Framework::builder()
         .options(FrameworkOptions {
@@ -112,7 +139,10 @@ async fn poise(
                 edit_loadout(),
                 help(),
                 playthrough(),
+                preferences(),
+                lfg(),
                 report(),
+                commands::issues::issues(),
                 db(),
                 wiki(),
             ],
@@ -128,16 +158,25 @@ async fn poise(
 
                 *loadouts_setup.write().await = LoadoutData::load(&pool).await;
                 *playthroughs_setup.write().await = PlaythroughData::load(&pool).await;
-                *issues.write().await = Issues::load(&ctx.http, &pool).await;
-
-                let guild_id: u64 = secret_store.get("ISSUE_GUILD").and_then(|id| id.parse().ok()).expect("issue guild should be valid and exists");
-                let guild_id = GuildId::from(guild_id);
-
-                let channel_id: u64 = secret_store.get("ISSUE_CHANNEL").and_then(|id| id.parse().ok()).expect("issue channel should be valid and exists");
-                let channel_id = ChannelId::from(channel_id);
-
-                let channels = guild_id.channels(&ctx.http).await?;
-                let issue_channel = channels.get(&channel_id).expect("channel exists");
+                *issues.write().await = Issues::load(&pool).await;
+                *preferences_setup.write().await = PreferenceData::load(&pool).await;
+                tokio::spawn(Issues::fill_author_names(ctx.clone(), issues.clone(), pool.clone()));
+
+                let issue_guild: u64 = secret_store.get("ISSUE_GUILD").and_then(|id| id.parse().ok()).expect("issue guild should be valid and exists");
+                let issue_guild = GuildId::from(issue_guild);
+
+                let issue_channel: u64 = secret_store.get("ISSUE_CHANNEL").and_then(|id| id.parse().ok()).expect("issue channel should be valid and exists");
+                let issue_channel = ChannelId::from(issue_channel);
+
+                // checked in the background so startup doesn't wait on Discord
+                let http = ctx.http.clone();
+                tokio::spawn(async move {
+                    match issue_guild.channels(&http).await {
+                        Ok(channels) if channels.contains_key(&issue_channel) => {},
+                        Ok(_) => error!("issue channel {issue_channel} isn't in issue guild {issue_guild}, so reports can't be posted"),
+                        Err(err) => error!("could not find the channels of issue guild {issue_guild}: {err}"),
+                    }
+                });
 
                 let all_guilds = ctx.cache.guild_count();
                 info!("loaded {} playthroughs", playthroughs_setup.read().await.active_playthroughs.len());
@@ -146,11 +185,13 @@ async fn poise(
                 info!("ready! logged in as {}", ready.user.tag());
                 Ok(Data {
                     pool,
-                    issue_channel: issue_channel.clone(),
+                    issue_channel,
+                    report_limits,
 
                     loadouts: loadouts_setup,
                     playthroughs: playthroughs_setup,
                     issues,
+                    preferences: preferences_setup,
                 })
             })
         })
@@ -160,30 +201,38 @@ async fn poise(
         .framework(framework)
         .await.expect("create client");
 
-    Ok(PoiseAxumService { poise: client, axum: web::app(loadouts, playthroughs) })
+    let scheduler = Scheduler {
+        http: client.http.clone(),
+        pool: pool_scheduler,
+        playthroughs: playthroughs_scheduler,
+        stale_after: TimeDelta::try_days(stale_days).expect("stale days is in range"),
+        archive_after: TimeDelta::try_days(archive_days).expect("archive days is in range"),
+        archive_paused_after: TimeDelta::try_days(archive_paused_days).expect("archive paused days is in range"),
+    };
+
+    Ok(PoiseAxumService { poise: client, axum: web::app(loadouts, playthroughs, issues_web, user_preferences, pool_web), scheduler })
 }
 
 async fn event_handler(ctx: &serenity::Context, event: &FullEvent, _framework: FrameworkContext<'_, Data, Error>, data: &Data) -> PoiseResult {
     match event {
         FullEvent::InteractionCreate { interaction: Interaction::Component(interaction) }
-            if matches!(interaction.data.kind, ComponentInteractionDataKind::Button) && interaction.data.custom_id.starts_with("r-") => {
-                if let Some(member) = &interaction.member {
-                    let id = {
-                        let guild = interaction.guild_id.expect("has guild id").to_guild_cached(ctx).expect("guild exists in cache");
-                        let channel = guild.channels.get(&interaction.channel_id).expect("channel exists");
-                        let permissions = guild.user_permissions_in(channel, member);
-                        if !permissions.administrator() { return Ok(()); }
-                        interaction.data.custom_id[2..].parse::<i32>().expect("issue id is a number")
-                    };
-
-                    let mut issues = data.issues.write().await;
-                    let issue = issues.resolve(id, &data.pool).await.map_err(|NoIssueFound(id)| format!("issue not found: {id}"))?;
-
-                    interaction.create_response(ctx, CreateInteractionResponse::UpdateMessage(CreateInteractionResponseMessage::new()
-                            .embed(issue.create_resolved_embed())
-                            .components(Vec::with_capacity(0))
-                    )).await?;
-                }
+            if interaction.data.custom_id.starts_with("issue-") || interaction.data.custom_id.starts_with("r-") => {
+                commands::issues::handle_interaction(ctx, interaction, data).await?;
+        }
+        FullEvent::InteractionCreate { interaction: Interaction::Component(interaction) } if interaction.data.custom_id.starts_with("issues-page-") => {
+            commands::issues::handle_page_interaction(ctx, interaction, data).await?;
+        }
+        FullEvent::InteractionCreate { interaction: Interaction::Modal(interaction) } if interaction.data.custom_id.starts_with("issue-") => {
+            commands::issues::handle_modal(ctx, interaction, data).await?;
+        }
+        FullEvent::InteractionCreate { interaction: Interaction::Component(interaction) } if interaction.data.custom_id.starts_with("lfg-") => {
+            commands::lfg::handle_interaction(ctx, interaction, data).await?;
+        }
+        FullEvent::InteractionCreate { interaction: Interaction::Component(interaction) } if interaction.data.custom_id.starts_with("vote-") => {
+            commands::playthrough::handle_vote_interaction(ctx, interaction, data).await?;
+        }
+        FullEvent::InteractionCreate { interaction: Interaction::Component(interaction) } if interaction.data.custom_id.starts_with("dash-") => {
+            commands::playthrough::handle_dashboard_interaction(ctx, interaction, data).await?;
         }
         _ => {},
     }
@@ -227,3 +276,78 @@ where
         .fold(String::new(), |prev, curr| prev + "\n" + &curr)
 }
 
+pub fn format_duration(duration: TimeDelta) -> String {
+    let units = [
+        (duration.num_days(), "day"),
+        (duration.num_hours() % 24, "hour"),
+        (duration.num_minutes() % 60, "minute"),
+    ];
+    let parts: Vec<String> = units.into_iter()
+        .filter(|(amount, _)| *amount > 0)
+        .map(|(amount, unit)| format!("{amount} {unit}{}", if amount == 1 { "" } else { "s" }))
+        .collect();
+
+    if parts.is_empty() {
+        let seconds = duration.num_seconds().max(0);
+        format!("{seconds} second{}", if seconds == 1 { "" } else { "s" })
+    } else {
+        parts.join(", ")
+    }
+}
+
+/// Parses a duration such as `1d 2h 30m` or `45m`
+pub fn parse_duration(input: &str) -> Option<TimeDelta> {
+    let mut total = TimeDelta::zero();
+    let mut amount = String::new();
+    for char in input.chars().filter(|char| !char.is_whitespace()) {
+        if char.is_ascii_digit() {
+            amount.push(char);
+            continue;
+        }
+        let value: i64 = amount.parse().ok()?;
+        amount.clear();
+        let delta = match char.to_ascii_lowercase() {
+            'w' => TimeDelta::try_weeks(value)?,
+            'd' => TimeDelta::try_days(value)?,
+            'h' => TimeDelta::try_hours(value)?,
+            'm' => TimeDelta::try_minutes(value)?,
+            _ => return None,
+        };
+        total = total.checked_add(&delta)?;
+    }
+
+    if !amount.is_empty() || total.is_zero() { return None }
+    Some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_duration, parse_duration, TimeDelta};
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!(format_duration(TimeDelta::try_minutes(1).unwrap()), "1 minute");
+        assert_eq!(format_duration(TimeDelta::try_hours(26).unwrap() + TimeDelta::try_minutes(5).unwrap()), "1 day, 2 hours, 5 minutes");
+        assert_eq!(format_duration(TimeDelta::try_days(2).unwrap() + TimeDelta::try_seconds(30).unwrap()), "2 days");
+        assert_eq!(format_duration(TimeDelta::try_seconds(45).unwrap()), "45 seconds");
+        assert_eq!(format_duration(TimeDelta::try_seconds(-5).unwrap()), "0 seconds");
+    }
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("45m"), TimeDelta::try_minutes(45));
+        assert_eq!(parse_duration("1d 2h 30m"), TimeDelta::try_minutes(24 * 60 + 2 * 60 + 30));
+        assert_eq!(parse_duration("1W"), TimeDelta::try_weeks(1));
+        assert_eq!(parse_duration("2h2h"), TimeDelta::try_hours(4));
+    }
+
+    #[test]
+    fn rejects_invalid_durations() {
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("0m"), None);
+        assert_eq!(parse_duration("30"), None);
+        assert_eq!(parse_duration("h"), None);
+        assert_eq!(parse_duration("5s"), None);
+        assert_eq!(parse_duration("99999999999999999999d"), None);
+        assert_eq!(parse_duration("100000000000d 100000000000d"), None);
+    }
+}
@@ -0,0 +1,266 @@
+use std::{collections::HashMap, io::Read};
+
+use flate2::read::GzDecoder;
+use thiserror::Error;
+
+/// Files are uploaded by users, so they can't decompress to more than this
+const MAX_DECOMPRESSED: u64 = 64 * 1024 * 1024;
+/// How deeply lists and compounds can be nested, which keeps malicious files from overflowing the stack
+const MAX_DEPTH: usize = 64;
+
+/// A tag in tModLoader's big-endian NBT format, used by `.twld` and `.tplr` files
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    End,
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<u8>),
+    String(String),
+    List(Vec<Tag>),
+    Compound(HashMap<String, Tag>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Tag {
+    pub fn get(&self, key: &str) -> Option<&Tag> {
+        match self {
+            Self::Compound(compound) => compound.get(key),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(str) => Some(str),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Tag]> {
+        match self {
+            Self::List(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    /// Visits this tag and every tag nested inside of it, along with the key it is stored under (if any)
+    pub fn walk<'a>(&'a self, visit: &mut impl FnMut(Option<&'a str>, &'a Tag)) {
+        fn walk_inner<'a>(key: Option<&'a str>, tag: &'a Tag, visit: &mut impl FnMut(Option<&'a str>, &'a Tag)) {
+            visit(key, tag);
+            match tag {
+                Tag::List(list) => list.iter().for_each(|tag| walk_inner(None, tag, visit)),
+                Tag::Compound(compound) => compound.iter().for_each(|(key, tag)| walk_inner(Some(key), tag, visit)),
+                _ => {},
+            }
+        }
+        walk_inner(None, self, visit);
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum NbtError {
+    #[error("unexpected end of data")]
+    UnexpectedEof,
+    #[error("unknown tag type {0}")]
+    UnknownTag(u8),
+    #[error("invalid UTF-8 string")]
+    InvalidString,
+    #[error("root tag is not a compound")]
+    RootNotCompound,
+    #[error("could not decompress data: {0}")]
+    Decompress(#[from] std::io::Error),
+    #[error("data is larger than {} MiB when decompressed", MAX_DECOMPRESSED / 1024 / 1024)]
+    TooLarge,
+    #[error("length {0} is longer than the remaining data")]
+    InvalidLength(usize),
+    #[error("tags are nested more than {MAX_DEPTH} levels deep")]
+    TooDeep,
+}
+
+/// Reads a gzip compressed NBT file, returning its root compound
+pub fn read_compressed(bytes: &[u8]) -> Result<Tag, NbtError> {
+    let mut decompressed = Vec::new();
+    // one byte more than the limit is read to tell data that is exactly at the limit apart from data that goes past it
+    GzDecoder::new(bytes).take(MAX_DECOMPRESSED + 1).read_to_end(&mut decompressed)?;
+    if decompressed.len() as u64 > MAX_DECOMPRESSED { return Err(NbtError::TooLarge) }
+    read(&decompressed)
+}
+
+/// Reads an uncompressed NBT file, returning its root compound
+pub fn read(bytes: &[u8]) -> Result<Tag, NbtError> {
+    let mut reader = NbtReader { bytes, pos: 0 };
+    let id = reader.u8()?;
+    if id != 10 { return Err(NbtError::RootNotCompound) }
+    reader.string()?;
+    reader.payload(id, 0)
+}
+
+struct NbtReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl NbtReader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], NbtError> {
+        let bytes = self.bytes.get(self.pos..self.pos + N).ok_or(NbtError::UnexpectedEof)?;
+        self.pos += N;
+        Ok(bytes.try_into().expect("slice has length N"))
+    }
+
+    fn u8(&mut self) -> Result<u8, NbtError> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn i32(&mut self) -> Result<i32, NbtError> {
+        Ok(i32::from_be_bytes(self.take()?))
+    }
+
+    fn i64(&mut self) -> Result<i64, NbtError> {
+        Ok(i64::from_be_bytes(self.take()?))
+    }
+
+    fn string(&mut self) -> Result<String, NbtError> {
+        let len = u16::from_be_bytes(self.take()?) as usize;
+        let bytes = self.bytes.get(self.pos..self.pos + len).ok_or(NbtError::UnexpectedEof)?;
+        self.pos += len;
+        String::from_utf8(bytes.to_vec()).map_err(|_| NbtError::InvalidString)
+    }
+
+    /// Reads the length of an array or list whose elements each take up at least `element_size` bytes, rejecting lengths that
+    /// couldn't fit in the rest of the data
+    fn len(&mut self, element_size: usize) -> Result<usize, NbtError> {
+        let len = self.i32()?.max(0) as usize;
+        if len.saturating_mul(element_size) > self.bytes.len() - self.pos { return Err(NbtError::InvalidLength(len)) }
+        Ok(len)
+    }
+
+    fn payload(&mut self, id: u8, depth: usize) -> Result<Tag, NbtError> {
+        if depth > MAX_DEPTH { return Err(NbtError::TooDeep) }
+
+        let tag = match id {
+            0 => Tag::End,
+            1 => Tag::Byte(i8::from_be_bytes(self.take()?)),
+            2 => Tag::Short(i16::from_be_bytes(self.take()?)),
+            3 => Tag::Int(self.i32()?),
+            4 => Tag::Long(self.i64()?),
+            5 => Tag::Float(f32::from_be_bytes(self.take()?)),
+            6 => Tag::Double(f64::from_be_bytes(self.take()?)),
+            7 => {
+                let len = self.len(1)?;
+                let bytes = self.bytes.get(self.pos..self.pos + len).ok_or(NbtError::UnexpectedEof)?;
+                self.pos += len;
+                Tag::ByteArray(bytes.to_vec())
+            },
+            8 => Tag::String(self.string()?),
+            9 => {
+                let element_id = self.u8()?;
+                let element_size = match element_id {
+                    1 | 10 => 1,
+                    2 | 8 => 2,
+                    3 | 5 | 7 | 11 | 12 => 4,
+                    4 | 6 => 8,
+                    9 => 5,
+                    // lists of end tags are only valid when they're empty, since they take up no bytes
+                    _ => usize::MAX,
+                };
+                let len = self.len(element_size)?;
+                Tag::List((0..len).map(|_| self.payload(element_id, depth + 1)).collect::<Result<_, _>>()?)
+            },
+            10 => {
+                let mut compound = HashMap::new();
+                loop {
+                    let id = self.u8()?;
+                    if id == 0 { break }
+                    let key = self.string()?;
+                    compound.insert(key, self.payload(id, depth + 1)?);
+                }
+                Tag::Compound(compound)
+            },
+            11 => {
+                let len = self.len(4)?;
+                Tag::IntArray((0..len).map(|_| self.i32()).collect::<Result<_, _>>()?)
+            },
+            12 => {
+                let len = self.len(8)?;
+                Tag::LongArray((0..len).map(|_| self.i64()).collect::<Result<_, _>>()?)
+            },
+            id => return Err(NbtError::UnknownTag(id)),
+        };
+        Ok(tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{write::GzEncoder, Compression};
+
+    use super::{read, read_compressed, NbtError, Tag, MAX_DECOMPRESSED, MAX_DEPTH};
+
+    /// A root compound holding a single tag named "a" with the given id and payload
+    fn root(id: u8, payload: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![10, 0, 0, id, 0, 1, b'a'];
+        bytes.extend_from_slice(payload);
+        bytes.push(0);
+        bytes
+    }
+
+    fn compress(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+        encoder.write_all(bytes).expect("data compresses");
+        encoder.finish().expect("data compresses")
+    }
+
+    #[test]
+    fn reads_tags() {
+        let mut list = vec![3];
+        list.extend_from_slice(&2i32.to_be_bytes());
+        list.extend_from_slice(&7i32.to_be_bytes());
+        list.extend_from_slice(&8i32.to_be_bytes());
+
+        let tag = read_compressed(&compress(&root(9, &list))).expect("nbt is valid");
+        assert_eq!(tag.get("a").and_then(Tag::as_list), Some(&[Tag::Int(7), Tag::Int(8)][..]));
+    }
+
+    #[test]
+    fn rejects_data_that_decompresses_too_far() {
+        let bomb = compress(&vec![0; MAX_DECOMPRESSED as usize + 1]);
+        assert!(matches!(read_compressed(&bomb), Err(NbtError::TooLarge)));
+    }
+
+    #[test]
+    fn rejects_lengths_past_the_end() {
+        let mut list = vec![0];
+        list.extend_from_slice(&i32::MAX.to_be_bytes());
+        assert!(matches!(read(&root(9, &list)), Err(NbtError::InvalidLength(_))));
+
+        let mut list = vec![3];
+        list.extend_from_slice(&i32::MAX.to_be_bytes());
+        assert!(matches!(read(&root(9, &list)), Err(NbtError::InvalidLength(_))));
+
+        assert!(matches!(read(&root(11, &i32::MAX.to_be_bytes())), Err(NbtError::InvalidLength(_))));
+
+        let mut empty = vec![0];
+        empty.extend_from_slice(&0i32.to_be_bytes());
+        assert_eq!(read(&root(9, &empty)).expect("nbt is valid").get("a"), Some(&Tag::List(Vec::new())));
+    }
+
+    #[test]
+    fn rejects_deep_nesting() {
+        // lists of a single list, nested past the limit
+        let mut nested = Vec::new();
+        for _ in 0..MAX_DEPTH + 1 {
+            nested.push(9);
+            nested.extend_from_slice(&1i32.to_be_bytes());
+        }
+        nested.push(0);
+        nested.extend_from_slice(&0i32.to_be_bytes());
+        assert!(matches!(read(&root(9, &nested)), Err(NbtError::TooDeep)));
+    }
+}
@@ -0,0 +1,183 @@
+use aes::Aes128;
+use cbc::cipher::{block_padding::Pkcs7, BlockDecryptMut, KeyIvInit};
+use convert_case::{Case, Casing};
+use thiserror::Error;
+
+use crate::nbt::{self, NbtError, Tag};
+
+/// Vanilla player files are AES encrypted, using the UTF-16 encoding of "h3y_gUyZ" as both the key and IV
+const KEY: &[u8; 16] = &[b'h', 0, b'3', 0, b'y', 0, b'_', 0, b'g', 0, b'U', 0, b'y', 0, b'Z', 0];
+/// The oldest player file version that can be read (Terraria 1.4.0)
+const MIN_VERSION: i32 = 225;
+const MAGIC: &[u8; 7] = b"relogic";
+const PLAYER_FILE_TYPE: u8 = 3;
+
+#[derive(Error, Debug)]
+pub enum PlayerFileError {
+    #[error("unexpected end of player file")]
+    UnexpectedEof,
+    #[error("this is not a Terraria player file")]
+    InvalidMagic,
+    #[error("player file version {0} is too old, please load the player in Terraria 1.4 first")]
+    UnsupportedVersion(i32),
+    #[error("player file contains an invalid string")]
+    InvalidString,
+    #[error("invalid tModLoader player file: {0}")]
+    Nbt(#[from] NbtError),
+}
+
+/// Every item a player has (inventory, armor, accessories, banks, etc.)
+#[derive(Debug, Default)]
+pub struct PlayerItems {
+    /// The names of the items stored in a tModLoader `.tplr` file
+    pub names: Vec<String>,
+    /// The IDs of the items stored in a vanilla `.plr` file, which only has IDs and no names
+    pub vanilla_ids: Vec<i32>,
+}
+
+/// Reads the items from either a vanilla `.plr` file or a tModLoader `.tplr` file
+pub fn parse_items(bytes: &[u8]) -> Result<PlayerItems, PlayerFileError> {
+    // .tplr files are always gzip compressed, while .plr files are encrypted
+    if bytes.starts_with(&[0x1F, 0x8B]) {
+        Ok(PlayerItems { names: parse_modded_items(bytes)?, ..Default::default() })
+    } else {
+        Ok(PlayerItems { vanilla_ids: parse_vanilla_items(bytes)?, ..Default::default() })
+    }
+}
+
+/// Reads the item IDs from the inventory, equipment and banks of a `.plr` file
+fn parse_vanilla_items(bytes: &[u8]) -> Result<Vec<i32>, PlayerFileError> {
+    let mut buffer = bytes.to_vec();
+    let bytes = cbc::Decryptor::<Aes128>::new(KEY.into(), KEY.into())
+        .decrypt_padded_mut::<Pkcs7>(&mut buffer)
+        .map_err(|_| PlayerFileError::InvalidMagic)?;
+    let mut reader = PlayerReader { bytes, pos: 0 };
+
+    let version = reader.i32()?;
+    if version < MIN_VERSION { return Err(PlayerFileError::UnsupportedVersion(version)) }
+    if reader.take::<7>()? != *MAGIC || reader.u8()? != PLAYER_FILE_TYPE { return Err(PlayerFileError::InvalidMagic) }
+    reader.skip(4 + 8)?; // revision, favorite flags
+
+    reader.string()?; // name
+    reader.skip(1 + 8 + 4 + 1 + 2 + 1 + 1)?; // difficulty, play time, hair, hair dye, hidden accessories, hidden misc, skin variant
+    reader.skip(4 * 4 + 1)?; // life, max life, mana, max mana, extra accessory slot
+
+    // unlocked biome torches, using biome torches, artisan loaf, then the aegis crystal, aegis fruit, arcane crystal, galaxy pearl,
+    // gummy worm and ambrosia which were each added in later versions
+    let consumables = [229, 229, 256, 260, 260, 260, 260, 260, 260].iter().filter(|added| version >= **added).count();
+    reader.skip(consumables)?;
+    reader.skip(1 + 4)?; // old one's army, tax money
+    if version >= 254 { reader.skip(4 * 2)? } // pve and pvp deaths
+    reader.skip(3 * 7)?; // hair, skin, eye, shirt, undershirt, pants and shoe colors
+
+    let mut ids = Vec::new();
+    for _ in 0..20 + 10 { // armor, accessories and vanity, dyes
+        ids.push(reader.i32()?);
+        reader.skip(1)?; // prefix
+    }
+    for _ in 0..58 {
+        ids.push(reader.i32()?);
+        reader.skip(4 + 1 + 1)?; // stack, prefix, favorited
+    }
+    for _ in 0..5 * 2 { // misc equips and their dyes
+        ids.push(reader.i32()?);
+        reader.skip(1)?; // prefix
+    }
+    for bank in 0..4 { // piggy bank, safe, defender's forge, void vault
+        for _ in 0..40 {
+            ids.push(reader.i32()?);
+            reader.skip(4 + 1)?; // stack, prefix
+            if bank == 3 && version >= 255 { reader.skip(1)? } // favorited
+        }
+    }
+
+    let mut items = Vec::new();
+    for id in ids.into_iter().filter(|id| *id > 0) {
+        if !items.contains(&id) { items.push(id) }
+    }
+    Ok(items)
+}
+
+/// Reads the names of the items saved by tModLoader, which are modded items and vanilla items with modded data
+fn parse_modded_items(bytes: &[u8]) -> Result<Vec<String>, PlayerFileError> {
+    let root = nbt::read_compressed(bytes)?;
+
+    let mut items = Vec::new();
+    root.walk(&mut |_, tag| {
+        let (Some(mod_name), Some(name)) = (tag.get("mod").and_then(Tag::as_str), tag.get("name").and_then(Tag::as_str)) else { return };
+        // unloaded items from mods that are no longer enabled
+        if mod_name == "ModLoader" { return }
+        let name = name.from_case(Case::Pascal).to_case(Case::Title);
+        if !items.contains(&name) { items.push(name) }
+    });
+
+    Ok(items)
+}
+
+struct PlayerReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl PlayerReader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], PlayerFileError> {
+        let bytes = self.bytes.get(self.pos..self.pos + N).ok_or(PlayerFileError::UnexpectedEof)?;
+        self.pos += N;
+        Ok(bytes.try_into().expect("slice has length N"))
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), PlayerFileError> {
+        if self.pos + len > self.bytes.len() { return Err(PlayerFileError::UnexpectedEof) }
+        self.pos += len;
+        Ok(())
+    }
+
+    fn u8(&mut self) -> Result<u8, PlayerFileError> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn i32(&mut self) -> Result<i32, PlayerFileError> {
+        Ok(i32::from_le_bytes(self.take()?))
+    }
+
+    /// Reads a .NET string, which is prefixed by its length as a 7-bit encoded integer
+    fn string(&mut self) -> Result<String, PlayerFileError> {
+        let mut len = 0;
+        for shift in (0..35).step_by(7) {
+            let byte = self.u8()?;
+            len |= ((byte & 0x7F) as usize) << shift;
+            if byte & 0x80 == 0 { break }
+        }
+        let bytes = self.bytes.get(self.pos..self.pos + len).ok_or(PlayerFileError::UnexpectedEof)?;
+        self.pos += len;
+        String::from_utf8(bytes.to_vec()).map_err(|_| PlayerFileError::InvalidString)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_items, PlayerFileError};
+
+    #[test]
+    fn parses_vanilla_player() {
+        let items = parse_items(include_bytes!("../tests/fixtures/player.plr")).expect("player parses");
+
+        assert!(items.names.is_empty());
+        // minishark and hermes boots in the inventory, cloud in a bottle equipped and the starfury in the piggy bank
+        assert_eq!(items.vanilla_ids, vec![53, 98, 54, 65]);
+    }
+
+    #[test]
+    fn parses_modded_player() {
+        let items = parse_items(include_bytes!("../tests/fixtures/player.tplr")).expect("player parses");
+
+        assert!(items.vanilla_ids.is_empty());
+        assert_eq!(items.names, vec!["Wulfrum Knife", "Hermes Boots"]);
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        assert!(matches!(parse_items(b"not a player"), Err(PlayerFileError::InvalidMagic)));
+        assert!(parse_items(&[0x1F, 0x8B, 0, 0]).is_err());
+    }
+}
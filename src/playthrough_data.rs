@@ -1,20 +1,56 @@
 use std::{collections::{HashMap, HashSet}, vec::Vec, convert::Into};
 
 use multimap::MultiMap;
+use num_derive::FromPrimitive;
 use num_traits::{FromPrimitive, ToPrimitive};
-use poise::serenity_prelude::{UserId, User};
+use poise::{ChoiceParameter, serenity_prelude::{ChannelId, GuildId, MessageId, RoleId, UserId, User}};
 use serde::Serialize;
 use sqlx::{PgPool, types::{BigDecimal, chrono::{NaiveDateTime, Utc}}};
 use tracing::info;
 
-use crate::loadout_data::{CalamityClass, Stage};
+use crate::{boss::Boss, loadout_data::{CalamityClass, Stage}, vote::{ProgressVote, VoteOutcome}, TimeDelta};
 
-type RawPlaythrough = (BigDecimal, i16, Option<NaiveDateTime>);
+type RawPlaythrough = (
+    BigDecimal,
+    i16,
+    Option<NaiveDateTime>,
+    NaiveDateTime,
+    Option<NaiveDateTime>,
+    Option<BigDecimal>,
+    Option<BigDecimal>,
+    Option<BigDecimal>,
+    Option<BigDecimal>,
+    Option<BigDecimal>,
+    Option<BigDecimal>,
+);
 
-type RawPlayer = (BigDecimal, BigDecimal, i16);
+type RawPlayer = (BigDecimal, BigDecimal, i16, Option<i16>);
+
+type RawDefeatedBoss = (BigDecimal, i16);
+
+type RawPause = (BigDecimal, NaiveDateTime, Option<NaiveDateTime>);
+
+type RawReminder = (BigDecimal, NaiveDateTime);
+
+type RawStageChange = (BigDecimal, i16, NaiveDateTime);
+
+type RawListing = (BigDecimal, String, String, Vec<i16>, NaiveDateTime);
+
+type RawSettings = (BigDecimal, Option<i16>, Option<String>, Option<i16>, Option<String>, Option<i16>, Vec<String>, Option<String>, bool, Option<i16>);
+
+type RawVote = (BigDecimal, i16, BigDecimal, BigDecimal, BigDecimal, NaiveDateTime);
+
+type RawBallot = (BigDecimal, BigDecimal, bool);
 
 pub struct InPlaythroughError;
 
+pub struct NotInPlaythroughError;
+
+pub enum ChangeClassError {
+    NotInPlaythrough,
+    ClassTaken,
+}
+
 pub enum FinishPlaythroughError {
     NotInPlaythrough,
     NotOwner,
@@ -30,6 +66,7 @@ pub enum JoinPlayerError {
     AlreadyInPlaythrough,
     PlayerNotInPlaythrough,
     PlayerNotOwner,
+    ClassTaken,
 }
 
 pub enum KickError {
@@ -50,21 +87,78 @@ pub enum ProgressError {
     LastStage,
 }
 
+pub enum StartVoteError {
+    NotInPlaythrough,
+    VotingDisabled,
+    VoteInProgress,
+}
+
+pub enum CastVoteError {
+    NoVote,
+    NotPlayer,
+}
+
+pub enum PauseError {
+    NotInPlaythrough,
+    NotOwner,
+    NotStarted,
+    AlreadyPaused,
+}
+
+pub enum ResumeError {
+    NotInPlaythrough,
+    NotOwner,
+    AlreadyRunning,
+}
+
+pub enum RemindError {
+    NotInPlaythrough,
+    NotOwner,
+}
+
+pub enum SetChannelError {
+    NotInPlaythrough,
+    NotOwner,
+}
+
+pub enum SetRoleError {
+    NotInPlaythrough,
+    NotOwner,
+}
+
+pub enum UpdateSettingsError {
+    NotInPlaythrough,
+    NotOwner,
+}
+
+pub enum SetListingError {
+    NotInPlaythrough,
+    NotOwner,
+}
+
+pub enum DefeatBossError {
+    NotInPlaythrough,
+    AlreadyDefeated,
+}
+
 #[derive(Default)]
 pub struct PlaythroughData {
     pub active_playthroughs: HashMap<UserId, Playthrough>,
     pub all_users: HashSet<UserId>,
+    /// Votes that were closed because their playthrough moved on, whose polls still show buttons
+    cancelled_votes: Vec<(UserId, ProgressVote)>,
 }
 
 impl PlaythroughData {
-    pub async fn create(&mut self, owner: &User, class: CalamityClass, pool: &PgPool) -> Result<&Playthrough, InPlaythroughError> {
+    pub async fn create(&mut self, owner: &User, class: CalamityClass, guild: Option<GuildId>, pool: &PgPool) -> Result<&Playthrough, InPlaythroughError> {
         if self.all_users.contains(&owner.id) { return Err(InPlaythroughError) }
 
         let owner_id = BigDecimal::from(owner.id.get());
 
-        sqlx::query("INSERT INTO playthroughs(owner, stage) VALUES ($1, $2)")
+        sqlx::query("INSERT INTO playthroughs(owner, stage, guild_id) VALUES ($1, $2, $3)")
             .bind(owner_id.clone())
             .bind(Stage::default() as i16)
+            .bind(guild.map(|guild| BigDecimal::from(guild.get())))
             .execute(pool).await.expect("valid query");
 
         sqlx::query("INSERT INTO playthrough_players(playthrough_owner, user_id, class) VALUES ($1, $2, $3)")
@@ -75,9 +169,22 @@ impl PlaythroughData {
 
         let playthrough = Playthrough {
             owner: owner.id,
-            players: vec![Player { user_id: owner.id, class }],
+            players: vec![Player { user_id: owner.id, class, stage: None }],
             stage: Default::default(),
             started: None,
+            defeated: Vec::new(),
+            pauses: Vec::new(),
+            reminders: Vec::new(),
+            last_activity: Utc::now().naive_utc(),
+            stale_notified: None,
+            guild,
+            channel: None,
+            role: None,
+            settings: PlaythroughSettings::default(),
+            listing: None,
+            stage_history: Vec::new(),
+            vote: None,
+            dashboard: None,
         };
 
         let owner_id = playthrough.owner;
@@ -98,16 +205,35 @@ impl PlaythroughData {
             }
         }
 
+        Ok(self.remove(owner.id, pool).await)
+    }
+
+    /// Ends an abandoned playthrough, keeping a record of it in the archive
+    pub async fn archive(&mut self, owner: UserId, pool: &PgPool) -> Option<Playthrough> {
+        let playthrough = self.active_playthroughs.get(&owner)?;
+
+        sqlx::query("INSERT INTO archived_playthroughs(owner, stage, started) VALUES ($1, $2, $3)")
+            .bind(BigDecimal::from(owner.get()))
+            .bind(playthrough.stage as i16)
+            .bind(playthrough.started)
+            .execute(pool).await.expect("query works");
+
+        info!("archived playthrough owned by {}", owner);
+
+        Some(self.remove(owner, pool).await)
+    }
+
+    async fn remove(&mut self, owner: UserId, pool: &PgPool) -> Playthrough {
         sqlx::query("DELETE FROM playthroughs WHERE owner = $1")
-            .bind(BigDecimal::from(owner.id.get()))
+            .bind(BigDecimal::from(owner.get()))
             .execute(pool).await.expect("query works");
 
-        let playthrough = self.active_playthroughs.remove(&owner.id).expect("owner is in playthrough");
+        let playthrough = self.active_playthroughs.remove(&owner).expect("owner is in playthrough");
         playthrough.players.iter().for_each(|player| {
             self.all_users.remove(&player.user_id);
         });
 
-        Ok(playthrough)
+        playthrough
     }
 
     pub async fn start(&mut self, owner: &User, pool: &PgPool) -> Result<(), StartPlaythroughError> {
@@ -127,16 +253,258 @@ impl PlaythroughData {
             .execute(pool).await.expect("query works");
 
         playthrough.started = Some(now);
+        playthrough.record_activity(pool).await;
+        playthrough.record_stage(pool).await;
+
+        Ok(())
+    }
+
+    pub async fn pause(&mut self, owner: &User, pool: &PgPool) -> Result<(), PauseError> {
+        let playthrough = match self.active_playthroughs.get_mut(&owner.id) {
+            Some(playthrough) => Ok(playthrough),
+            None if self.all_users.contains(&owner.id) => Err(PauseError::NotOwner),
+            None => Err(PauseError::NotInPlaythrough),
+        }?;
+        if playthrough.started.is_none() { return Err(PauseError::NotStarted) }
+        if playthrough.is_paused() { return Err(PauseError::AlreadyPaused) }
+
+        let now = Utc::now().naive_utc();
+        sqlx::query("INSERT INTO playthrough_pauses(playthrough_owner, paused) VALUES ($1, $2)")
+            .bind(BigDecimal::from(owner.id.get()))
+            .bind(now)
+            .execute(pool).await.expect("query works");
+
+        playthrough.pauses.push(Pause { paused: now, resumed: None });
 
         Ok(())
     }
 
+    pub async fn resume(&mut self, owner: &User, pool: &PgPool) -> Result<TimeDelta, ResumeError> {
+        let playthrough = match self.active_playthroughs.get_mut(&owner.id) {
+            Some(playthrough) => Ok(playthrough),
+            None if self.all_users.contains(&owner.id) => Err(ResumeError::NotOwner),
+            None => Err(ResumeError::NotInPlaythrough),
+        }?;
+        let pause = playthrough.pauses.last_mut()
+            .filter(|pause| pause.resumed.is_none())
+            .ok_or(ResumeError::AlreadyRunning)?;
+
+        let now = Utc::now().naive_utc();
+        sqlx::query("UPDATE playthrough_pauses SET resumed = $1 WHERE playthrough_owner = $2 AND paused = $3")
+            .bind(now)
+            .bind(BigDecimal::from(owner.id.get()))
+            .bind(pause.paused)
+            .execute(pool).await.expect("query works");
+
+        let paused_for = now - pause.paused;
+        pause.resumed = Some(now);
+        playthrough.record_activity(pool).await;
+
+        Ok(paused_for)
+    }
+
+    pub async fn remind(&mut self, owner: &User, remind_at: NaiveDateTime, pool: &PgPool) -> Result<(), RemindError> {
+        let playthrough = match self.active_playthroughs.get_mut(&owner.id) {
+            Some(playthrough) => Ok(playthrough),
+            None if self.all_users.contains(&owner.id) => Err(RemindError::NotOwner),
+            None => Err(RemindError::NotInPlaythrough),
+        }?;
+
+        sqlx::query("INSERT INTO playthrough_reminders(playthrough_owner, remind_at) VALUES ($1, $2)")
+            .bind(BigDecimal::from(owner.id.get()))
+            .bind(remind_at)
+            .execute(pool).await.expect("query works");
+
+        playthrough.reminders.push(remind_at);
+
+        Ok(())
+    }
+
+    /// Removes every reminder that is due, returning the owners of the playthroughs they were for
+    pub async fn take_due_reminders(&mut self, now: NaiveDateTime, pool: &PgPool) -> Vec<UserId> {
+        let mut owners = Vec::new();
+        for playthrough in self.active_playthroughs.values_mut() {
+            if !playthrough.reminders.iter().any(|remind_at| *remind_at <= now) { continue }
+            playthrough.reminders.retain(|remind_at| *remind_at > now);
+            owners.push(playthrough.owner);
+        }
+
+        if !owners.is_empty() {
+            sqlx::query("DELETE FROM playthrough_reminders WHERE remind_at <= $1")
+                .bind(now)
+                .execute(pool).await.expect("query works");
+        }
+
+        owners
+    }
+
+    /// Links a playthrough to the channel (or thread) its events are announced in. Passing `None` stops announcing events
+    pub async fn set_channel(&mut self, owner: &User, guild: GuildId, channel: Option<ChannelId>, pool: &PgPool) -> Result<&Playthrough, SetChannelError> {
+        let playthrough = match self.active_playthroughs.get_mut(&owner.id) {
+            Some(playthrough) => Ok(playthrough),
+            None if self.all_users.contains(&owner.id) => Err(SetChannelError::NotOwner),
+            None => Err(SetChannelError::NotInPlaythrough),
+        }?;
+
+        sqlx::query("UPDATE playthroughs SET guild_id = $1, channel_id = $2 WHERE owner = $3")
+            .bind(BigDecimal::from(guild.get()))
+            .bind(channel.map(|channel| BigDecimal::from(channel.get())))
+            .bind(BigDecimal::from(owner.id.get()))
+            .execute(pool).await.expect("query works");
+
+        playthrough.guild = Some(guild);
+        playthrough.channel = channel;
+
+        Ok(playthrough)
+    }
+
+    /// Saves the role given to players of a playthrough, along with the guild it was created in. Passing `None` removes the role
+    pub async fn set_role(&mut self, owner: &User, role: Option<(GuildId, RoleId)>, pool: &PgPool) -> Result<&Playthrough, SetRoleError> {
+        let playthrough = match self.active_playthroughs.get_mut(&owner.id) {
+            Some(playthrough) => Ok(playthrough),
+            None if self.all_users.contains(&owner.id) => Err(SetRoleError::NotOwner),
+            None => Err(SetRoleError::NotInPlaythrough),
+        }?;
+
+        sqlx::query("UPDATE playthroughs SET role_guild_id = $1, role_id = $2 WHERE owner = $3")
+            .bind(role.map(|(guild, _)| BigDecimal::from(guild.get())))
+            .bind(role.map(|(_, role)| BigDecimal::from(role.get())))
+            .bind(BigDecimal::from(owner.id.get()))
+            .execute(pool).await.expect("query works");
+
+        playthrough.role = role;
+
+        Ok(playthrough)
+    }
+
+    pub async fn update_settings(
+        &mut self,
+        owner: &User,
+        update: impl FnOnce(&mut PlaythroughSettings),
+        pool: &PgPool,
+    ) -> Result<&Playthrough, UpdateSettingsError> {
+        let playthrough = match self.active_playthroughs.get_mut(&owner.id) {
+            Some(playthrough) => Ok(playthrough),
+            None if self.all_users.contains(&owner.id) => Err(UpdateSettingsError::NotOwner),
+            None => Err(UpdateSettingsError::NotInPlaythrough),
+        }?;
+
+        update(&mut playthrough.settings);
+        let settings = &playthrough.settings;
+
+        sqlx::query("INSERT INTO playthrough_settings(playthrough_owner, difficulty, world_name, world_size, seed, evil, mods, notes, unique_classes,
+                vote_majority)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                ON CONFLICT (playthrough_owner) DO UPDATE SET difficulty = $2, world_name = $3, world_size = $4, seed = $5, evil = $6,
+                mods = $7, notes = $8, unique_classes = $9, vote_majority = $10")
+            .bind(BigDecimal::from(owner.id.get()))
+            .bind(settings.difficulty.map(|difficulty| difficulty as i16))
+            .bind(&settings.world_name)
+            .bind(settings.world_size.map(|size| size as i16))
+            .bind(&settings.seed)
+            .bind(settings.evil.map(|evil| evil as i16))
+            .bind(&settings.mods)
+            .bind(&settings.notes)
+            .bind(settings.unique_classes)
+            .bind(settings.vote_majority.map(|majority| majority as i16))
+            .execute(pool).await.expect("query works");
+
+        Ok(playthrough)
+    }
+
+    /// Saves the message showing the dashboard of the playthrough `player` is in, returning the previous dashboard message
+    pub async fn set_dashboard(
+        &mut self,
+        player: &User,
+        dashboard: Option<(ChannelId, MessageId)>,
+        pool: &PgPool,
+    ) -> Result<Option<(ChannelId, MessageId)>, NotInPlaythroughError> {
+        let playthrough = self.get_playthrough_mut(player.id).ok_or(NotInPlaythroughError)?;
+
+        sqlx::query("UPDATE playthroughs SET dashboard_channel_id = $1, dashboard_message_id = $2 WHERE owner = $3")
+            .bind(dashboard.map(|(channel, _)| BigDecimal::from(channel.get())))
+            .bind(dashboard.map(|(_, message)| BigDecimal::from(message.get())))
+            .bind(BigDecimal::from(playthrough.owner.get()))
+            .execute(pool).await.expect("query works");
+
+        Ok(std::mem::replace(&mut playthrough.dashboard, dashboard))
+    }
+
+    /// Switches the class `player` is playing in their playthrough
+    pub async fn change_class(&mut self, player: &User, class: CalamityClass, pool: &PgPool) -> Result<&Playthrough, ChangeClassError> {
+        let playthrough = self.get_playthrough_mut(player.id).ok_or(ChangeClassError::NotInPlaythrough)?;
+        if playthrough.settings.unique_classes && playthrough.players.iter().any(|p| p.class == class && p.user_id != player.id) {
+            return Err(ChangeClassError::ClassTaken)
+        }
+
+        sqlx::query("UPDATE playthrough_players SET class = $1 WHERE user_id = $2")
+            .bind(class as i16)
+            .bind(BigDecimal::from(player.id.get()))
+            .execute(pool).await.expect("query works");
+
+        let player_data = playthrough.players.iter_mut().find(|p| p.user_id == player.id).expect("player is in their playthrough");
+        player_data.class = class;
+        playthrough.record_activity(pool).await;
+
+        Ok(playthrough)
+    }
+
+    /// Lists a playthrough on the looking-for-group board. Passing `None` makes the playthrough private again
+    pub async fn set_listing(&mut self, owner: &User, listing: Option<Listing>, pool: &PgPool) -> Result<&Playthrough, SetListingError> {
+        let playthrough = match self.active_playthroughs.get_mut(&owner.id) {
+            Some(playthrough) => Ok(playthrough),
+            None if self.all_users.contains(&owner.id) => Err(SetListingError::NotOwner),
+            None => Err(SetListingError::NotInPlaythrough),
+        }?;
+
+        let owner_id = BigDecimal::from(owner.id.get());
+        match &listing {
+            Some(listing) => sqlx::query("INSERT INTO playthrough_listings(playthrough_owner, owner_name, description, wanted_classes, listed_at)
+                    VALUES ($1, $2, $3, $4, $5)
+                    ON CONFLICT (playthrough_owner) DO UPDATE SET owner_name = $2, description = $3, wanted_classes = $4, listed_at = $5")
+                .bind(owner_id)
+                .bind(&listing.owner_name)
+                .bind(&listing.description)
+                .bind(listing.wanted_classes.iter().map(|class| *class as i16).collect::<Vec<_>>())
+                .bind(listing.listed_at),
+            None => sqlx::query("DELETE FROM playthrough_listings WHERE playthrough_owner = $1")
+                .bind(owner_id),
+        }.execute(pool).await.expect("query works");
+
+        playthrough.listing = listing;
+
+        Ok(playthrough)
+    }
+
+    /// Every playthrough listed on the looking-for-group board, newest first
+    pub fn listings(&self) -> Vec<&Playthrough> {
+        let mut listings: Vec<&Playthrough> = self.active_playthroughs.values()
+            .filter(|playthrough| playthrough.listing.is_some())
+            .collect();
+        listings.sort_by_key(|playthrough| playthrough.listing.as_ref().map(|listing| listing.listed_at));
+        listings.reverse();
+        listings
+    }
+
+    pub async fn mark_stale_notified(&mut self, owner: UserId, pool: &PgPool) {
+        let Some(playthrough) = self.active_playthroughs.get_mut(&owner) else { return };
+
+        let now = Utc::now().naive_utc();
+        sqlx::query("UPDATE playthroughs SET stale_notified = $1 WHERE owner = $2")
+            .bind(now)
+            .bind(BigDecimal::from(owner.get()))
+            .execute(pool).await.expect("query works");
+
+        playthrough.stale_notified = Some(now);
+    }
+
     pub async fn join_player(&mut self, owner: &User, player: Player, pool: &PgPool) -> Result<(), JoinPlayerError> {
         let owner_id = owner.id;
         if self.all_users.contains(&player.user_id) { return Err(JoinPlayerError::AlreadyInPlaythrough) }
         if !self.all_users.contains(&owner_id) { return Err(JoinPlayerError::PlayerNotInPlaythrough) };
 
         let playthrough = self.active_playthroughs.get_mut(&owner_id).ok_or(JoinPlayerError::PlayerNotOwner)?;
+        if !playthrough.class_available(player.class) { return Err(JoinPlayerError::ClassTaken) }
 
         sqlx::query("INSERT INTO playthrough_players(playthrough_owner, user_id, class) VALUES ($1, $2, $3)")
             .bind(BigDecimal::from(owner_id.get()))
@@ -146,6 +514,7 @@ impl PlaythroughData {
 
         self.all_users.insert(player.user_id);
         playthrough.players.push(player);
+        playthrough.record_activity(pool).await;
 
         Ok(())
     }
@@ -185,10 +554,10 @@ impl PlaythroughData {
         Ok(playthrough)
     }
 
-    pub async fn progress(&mut self, owner: &User, stage: Option<Stage>, pool: &PgPool) -> Result<&Playthrough, ProgressError> {
-        if !self.all_users.contains(&owner.id) { return Err(ProgressError::NotInPlaythrough) }
+    pub async fn progress(&mut self, owner: UserId, stage: Option<Stage>, pool: &PgPool) -> Result<&Playthrough, ProgressError> {
+        if !self.all_users.contains(&owner) { return Err(ProgressError::NotInPlaythrough) }
 
-        let playthrough = self.active_playthroughs.get_mut(&owner.id).ok_or(ProgressError::NotOwner)?;
+        let playthrough = self.active_playthroughs.get_mut(&owner).ok_or(ProgressError::NotOwner)?;
 
         let new_stage = stage.or_else(|| {
             let stage_index = playthrough.stage as usize;
@@ -197,22 +566,226 @@ impl PlaythroughData {
 
         sqlx::query("UPDATE playthroughs SET stage = $1 WHERE owner = $2")
             .bind(new_stage as i16)
-            .bind(BigDecimal::from(owner.id.get()))
+            .bind(BigDecimal::from(owner.get()))
             .execute(pool).await.expect("query works");
 
+        let old_stage = playthrough.stage;
         playthrough.stage = new_stage;
+        playthrough.record_activity(pool).await;
+        playthrough.record_stage(pool).await;
+        playthrough.catch_up_players(old_stage, pool).await;
+        if let Some(vote) = playthrough.cancel_vote(pool).await {
+            self.cancelled_votes.push((owner, vote));
+        }
+
+        Ok(playthrough)
+    }
+
+    /// Changes the stage of only `player`, leaving the rest of their playthrough where it is. Progressing to the playthrough's stage
+    /// puts the player back in line with everyone else
+    pub async fn progress_player(&mut self, player: &User, stage: Option<Stage>, pool: &PgPool) -> Result<&Playthrough, ProgressError> {
+        let playthrough = self.get_playthrough_mut(player.id).ok_or(ProgressError::NotInPlaythrough)?;
+        let group_stage = playthrough.stage;
+        let player_data = playthrough.players.iter_mut().find(|p| p.user_id == player.id).expect("player is in their playthrough");
+
+        let new_stage = stage.or_else(|| {
+            let stage_index = player_data.stage.unwrap_or(group_stage) as usize;
+            FromPrimitive::from_usize(stage_index + 1)
+        }).ok_or(ProgressError::LastStage)?;
+        let new_stage = (new_stage != group_stage).then_some(new_stage);
+
+        sqlx::query("UPDATE playthrough_players SET stage = $1 WHERE user_id = $2")
+            .bind(new_stage.map(|stage| stage as i16))
+            .bind(BigDecimal::from(player.id.get()))
+            .execute(pool).await.expect("query works");
+
+        player_data.stage = new_stage;
+        playthrough.record_activity(pool).await;
 
         Ok(playthrough)
     }
 
+    /// Opens a vote to progress the playthrough `player` is in, with `player` voting in favor of it
+    pub async fn start_vote(&mut self, player: &User, vote: ProgressVote, pool: &PgPool) -> Result<VoteOutcome, StartVoteError> {
+        let playthrough = self.get_playthrough_mut(player.id).ok_or(StartVoteError::NotInPlaythrough)?;
+        let majority = playthrough.vote_majority()?;
+        let owner_id = BigDecimal::from(playthrough.owner.get());
+
+        sqlx::query("INSERT INTO progress_votes(playthrough_owner, stage, started_by, channel_id, message_id, expires_at) VALUES ($1, $2, $3, $4, $5, $6)")
+            .bind(owner_id.clone())
+            .bind(vote.stage as i16)
+            .bind(BigDecimal::from(player.id.get()))
+            .bind(BigDecimal::from(vote.channel.get()))
+            .bind(BigDecimal::from(vote.message.get()))
+            .bind(vote.expires_at)
+            .execute(pool).await.expect("query works");
+
+        sqlx::query("INSERT INTO progress_vote_ballots(playthrough_owner, user_id, approve) VALUES ($1, $2, true)")
+            .bind(owner_id)
+            .bind(BigDecimal::from(player.id.get()))
+            .execute(pool).await.expect("query works");
+
+        let vote = playthrough.vote.insert(ProgressVote { ballots: vec![(player.id, true)], ..vote });
+        Ok(vote.tally(&playthrough.players, majority))
+    }
+
+    /// Records `voter`'s vote in the open vote of `owner`'s playthrough, replacing their previous vote. `message` is the poll that was
+    /// voted on, which has to be the open vote's poll rather than one from an earlier vote
+    pub async fn cast_vote(
+        &mut self,
+        owner: UserId,
+        voter: UserId,
+        approve: bool,
+        message: MessageId,
+        pool: &PgPool,
+    ) -> Result<VoteOutcome, CastVoteError> {
+        let playthrough = self.active_playthroughs.get_mut(&owner).ok_or(CastVoteError::NoVote)?;
+        let vote = playthrough.vote.as_mut().filter(|vote| vote.message == message).ok_or(CastVoteError::NoVote)?;
+        if !playthrough.players.iter().any(|player| player.user_id == voter) { return Err(CastVoteError::NotPlayer) }
+
+        sqlx::query("INSERT INTO progress_vote_ballots(playthrough_owner, user_id, approve) VALUES ($1, $2, $3)
+                ON CONFLICT (playthrough_owner, user_id) DO UPDATE SET approve = $3")
+            .bind(BigDecimal::from(owner.get()))
+            .bind(BigDecimal::from(voter.get()))
+            .bind(approve)
+            .execute(pool).await.expect("query works");
+
+        vote.ballots.retain(|(user, _)| *user != voter);
+        vote.ballots.push((voter, approve));
+        Ok(vote.tally(&playthrough.players, playthrough.settings.vote_majority.unwrap_or(100)))
+    }
+
+    /// Closes the open vote of `owner`'s playthrough, returning it
+    pub async fn finish_vote(&mut self, owner: UserId, pool: &PgPool) -> Option<ProgressVote> {
+        let vote = self.active_playthroughs.get_mut(&owner)?.vote.take()?;
+
+        sqlx::query("DELETE FROM progress_votes WHERE playthrough_owner = $1")
+            .bind(BigDecimal::from(owner.get()))
+            .execute(pool).await.expect("query works");
+
+        Some(vote)
+    }
+
+    /// Closes every vote that expires before `now`, returning them along with the owner of their playthrough
+    pub async fn take_expired_votes(&mut self, now: NaiveDateTime, pool: &PgPool) -> Vec<(UserId, ProgressVote)> {
+        sqlx::query("DELETE FROM progress_votes WHERE expires_at <= $1")
+            .bind(now)
+            .execute(pool).await.expect("query works");
+
+        self.active_playthroughs.values_mut()
+            .filter(|playthrough| playthrough.vote.as_ref().is_some_and(|vote| vote.expires_at <= now))
+            .filter_map(|playthrough| Some((playthrough.owner, playthrough.vote.take()?)))
+            .collect()
+    }
+
+    /// Takes the votes closed since the last call because their playthrough moved on, along with the owner of their playthrough
+    pub fn take_cancelled_votes(&mut self) -> Vec<(UserId, ProgressVote)> {
+        std::mem::take(&mut self.cancelled_votes)
+    }
+
+    /// Marks a boss as defeated in the playthrough `player` is in. If every boss gating the current stage has been defeated, the
+    /// playthrough is advanced as far as the defeated bosses allow, returning the stage it was on before
+    pub async fn defeat(&mut self, player: &User, boss: Boss, pool: &PgPool) -> Result<(&Playthrough, Option<Stage>), DefeatBossError> {
+        let owner = self.get_playthrough(player.id).ok_or(DefeatBossError::NotInPlaythrough)?.owner;
+        let playthrough = self.active_playthroughs.get_mut(&owner).expect("playthrough exists");
+        if playthrough.defeated.contains(&boss) { return Err(DefeatBossError::AlreadyDefeated) }
+
+        let owner_id = BigDecimal::from(playthrough.owner.get());
+
+        sqlx::query("INSERT INTO playthrough_bosses(playthrough_owner, boss) VALUES ($1, $2)")
+            .bind(owner_id.clone())
+            .bind(boss as i16)
+            .execute(pool).await.expect("query works");
+
+        playthrough.defeated.push(boss);
+        playthrough.record_activity(pool).await;
+
+        let old_stage = playthrough.stage;
+        let new_stage = playthrough.stage_from_bosses();
+        if new_stage == old_stage { return Ok((playthrough, None)) }
+
+        sqlx::query("UPDATE playthroughs SET stage = $1 WHERE owner = $2")
+            .bind(new_stage as i16)
+            .bind(owner_id)
+            .execute(pool).await.expect("query works");
+
+        playthrough.stage = new_stage;
+        playthrough.record_stage(pool).await;
+        playthrough.catch_up_players(old_stage, pool).await;
+        if let Some(vote) = playthrough.cancel_vote(pool).await {
+            self.cancelled_votes.push((owner, vote));
+        }
+
+        Ok((playthrough, Some(old_stage)))
+    }
+
+    pub fn get_playthrough(&self, user: UserId) -> Option<&Playthrough> {
+        self.active_playthroughs.get(&user)
+            .or_else(|| self.active_playthroughs.values().find(|playthrough| playthrough.players.iter().any(|player| player.user_id == user)))
+    }
+
+    pub fn get_playthrough_mut(&mut self, user: UserId) -> Option<&mut Playthrough> {
+        if !self.all_users.contains(&user) { return None }
+        self.active_playthroughs.values_mut().find(|playthrough| playthrough.players.iter().any(|player| player.user_id == user))
+    }
+
     pub async fn load(pool: &PgPool) -> PlaythroughData {
-        let playthrough_data = sqlx::query_as("SELECT * FROM playthroughs")
+        let playthrough_data = sqlx::query_as("SELECT owner, stage, started, last_activity, stale_notified, guild_id, channel_id, role_guild_id, role_id,
+                dashboard_channel_id, dashboard_message_id FROM playthroughs")
+            .fetch_all(pool);
+
+        let players = sqlx::query_as("SELECT user_id, playthrough_owner, class, stage FROM playthrough_players")
+            .fetch_all(pool);
+
+        let defeated_bosses = sqlx::query_as("SELECT playthrough_owner, boss FROM playthrough_bosses ORDER BY defeated_at")
+            .fetch_all(pool);
+
+        let pauses = sqlx::query_as("SELECT playthrough_owner, paused, resumed FROM playthrough_pauses ORDER BY paused")
+            .fetch_all(pool);
+
+        let reminders = sqlx::query_as("SELECT playthrough_owner, remind_at FROM playthrough_reminders ORDER BY remind_at")
+            .fetch_all(pool);
+
+        let settings = sqlx::query_as("SELECT playthrough_owner, difficulty, world_name, world_size, seed, evil, mods, notes, unique_classes,
+                vote_majority FROM playthrough_settings")
+            .fetch_all(pool);
+
+        let listings = sqlx::query_as("SELECT playthrough_owner, owner_name, description, wanted_classes, listed_at FROM playthrough_listings")
+            .fetch_all(pool);
+
+        let stage_history = sqlx::query_as("SELECT playthrough_owner, stage, reached_at FROM playthrough_stages ORDER BY reached_at")
+            .fetch_all(pool);
+
+        let votes = sqlx::query_as("SELECT playthrough_owner, stage, started_by, channel_id, message_id, expires_at FROM progress_votes")
             .fetch_all(pool);
 
-        let players = sqlx::query_as("SELECT * FROM playthrough_players")
+        let ballots = sqlx::query_as("SELECT playthrough_owner, user_id, approve FROM progress_vote_ballots")
             .fetch_all(pool);
 
-        let (playthrough_data, players): (Vec<RawPlaythrough>, Vec<RawPlayer>) = tokio::try_join!(playthrough_data, players).expect("queries work");
+        #[allow(clippy::type_complexity)]
+        let (playthrough_data, players, defeated_bosses, pauses, reminders, settings, listings, stage_history, votes, ballots): (
+            Vec<RawPlaythrough>,
+            Vec<RawPlayer>,
+            Vec<RawDefeatedBoss>,
+            Vec<RawPause>,
+            Vec<RawReminder>,
+            Vec<RawSettings>,
+            Vec<RawListing>,
+            Vec<RawStageChange>,
+            Vec<RawVote>,
+            Vec<RawBallot>,
+        ) = tokio::try_join!(
+            playthrough_data,
+            players,
+            defeated_bosses,
+            pauses,
+            reminders,
+            settings,
+            listings,
+            stage_history,
+            votes,
+            ballots,
+        ).expect("queries work");
 
         let all_users: HashSet<UserId> = players.iter().map(|player| Into::<Player>::into(player).user_id).collect();
 
@@ -220,18 +793,104 @@ impl PlaythroughData {
             .map(|player| -> (BigDecimal, Player) { (player.1.clone(), player.into()) })
             .collect();
 
+        let defeated_bosses: MultiMap<BigDecimal, Boss> = defeated_bosses.into_iter()
+            .map(|(owner_id, boss)| (owner_id, Boss::from_i16(boss).expect("boss is a valid boss")))
+            .collect();
+
+        let pauses: MultiMap<BigDecimal, Pause> = pauses.into_iter()
+            .map(|(owner_id, paused, resumed)| (owner_id, Pause { paused, resumed }))
+            .collect();
+
+        let reminders: MultiMap<BigDecimal, NaiveDateTime> = reminders.into_iter().collect();
+        let stage_history: MultiMap<BigDecimal, (Stage, NaiveDateTime)> = stage_history.into_iter()
+            .map(|(owner_id, stage, reached_at)| (owner_id, (Stage::from_i16(stage).expect("stage is valid"), reached_at)))
+            .collect();
+        let mut settings: HashMap<BigDecimal, PlaythroughSettings> = settings.into_iter()
+            .map(|(owner_id, difficulty, world_name, world_size, seed, evil, mods, notes, unique_classes, vote_majority)| (owner_id, PlaythroughSettings {
+                difficulty: difficulty.map(|difficulty| Difficulty::from_i16(difficulty).expect("difficulty is valid")),
+                world_name,
+                world_size: world_size.map(|size| WorldSize::from_i16(size).expect("world size is valid")),
+                seed,
+                evil: evil.map(|evil| WorldEvil::from_i16(evil).expect("world evil is valid")),
+                mods,
+                notes,
+                unique_classes,
+                vote_majority: vote_majority.map(|majority| majority as u8),
+            }))
+            .collect();
+        let ballots: MultiMap<BigDecimal, (UserId, bool)> = ballots.into_iter()
+            .map(|(owner_id, user_id, approve)| (owner_id, (UserId::new(user_id.to_u64().expect("user snowflake is a valid u64")), approve)))
+            .collect();
+        let mut votes: HashMap<BigDecimal, ProgressVote> = votes.into_iter()
+            .map(|(owner_id, stage, started_by, channel, message, expires_at)| {
+                let vote = ProgressVote {
+                    stage: Stage::from_i16(stage).expect("stage is valid"),
+                    started_by: UserId::new(started_by.to_u64().expect("user snowflake is a valid u64")),
+                    channel: ChannelId::new(channel.to_u64().expect("channel snowflake is a valid u64")),
+                    message: MessageId::new(message.to_u64().expect("message snowflake is a valid u64")),
+                    expires_at,
+                    ballots: ballots.get_vec(&owner_id).cloned().unwrap_or_default(),
+                };
+                (owner_id, vote)
+            })
+            .collect();
+        let mut listings: HashMap<BigDecimal, Listing> = listings.into_iter()
+            .map(|(owner_id, owner_name, description, wanted_classes, listed_at)| (owner_id, Listing {
+                owner_name,
+                description,
+                wanted_classes: wanted_classes.into_iter().map(|class| CalamityClass::from_i16(class).expect("class is valid")).collect(),
+                listed_at,
+            }))
+            .collect();
+
         let mut playthroughs = HashMap::with_capacity(playthrough_data.len());
 
-        for (owner_id, stage, started) in playthrough_data {
+        for (owner_id, stage, started, last_activity, stale_notified, guild, channel, role_guild, role, dashboard_channel, dashboard_message) in playthrough_data {
             let players = players.get_vec(&owner_id).expect("valid playthrough id").clone();
+            let defeated = defeated_bosses.get_vec(&owner_id).cloned().unwrap_or_default();
+            let pauses = pauses.get_vec(&owner_id).cloned().unwrap_or_default();
+            let reminders = reminders.get_vec(&owner_id).cloned().unwrap_or_default();
+            let settings = settings.remove(&owner_id).unwrap_or_default();
+            let listing = listings.remove(&owner_id);
+            let vote = votes.remove(&owner_id);
+            let stage_history = stage_history.get_vec(&owner_id).cloned().unwrap_or_default();
             let owner_id = owner_id.to_u64().expect("owner snowflake is a valid u64");
             let stage = FromPrimitive::from_i16(stage).expect("stage is a valid stage");
-            playthroughs.insert(UserId::new(owner_id), Playthrough { owner: UserId::new(owner_id), players, stage, started });
+            let guild = guild.map(|guild| GuildId::new(guild.to_u64().expect("guild snowflake is a valid u64")));
+            let channel = channel.map(|channel| ChannelId::new(channel.to_u64().expect("channel snowflake is a valid u64")));
+            let role = role_guild.zip(role).map(|(guild, role)| (
+                GuildId::new(guild.to_u64().expect("guild snowflake is a valid u64")),
+                RoleId::new(role.to_u64().expect("role snowflake is a valid u64")),
+            ));
+            let dashboard = dashboard_channel.zip(dashboard_message).map(|(channel, message)| (
+                ChannelId::new(channel.to_u64().expect("channel snowflake is a valid u64")),
+                MessageId::new(message.to_u64().expect("message snowflake is a valid u64")),
+            ));
+            playthroughs.insert(UserId::new(owner_id), Playthrough {
+                owner: UserId::new(owner_id),
+                players,
+                stage,
+                started,
+                defeated,
+                pauses,
+                reminders,
+                last_activity,
+                stale_notified,
+                guild,
+                channel,
+                role,
+                settings,
+                listing,
+                stage_history,
+                vote,
+                dashboard,
+            });
         }
 
         PlaythroughData {
             active_playthroughs: playthroughs,
             all_users,
+            cancelled_votes: Vec::new(),
         }
     }
 }
@@ -242,12 +901,248 @@ pub struct Playthrough {
     pub players: Vec<Player>,
     pub stage: Stage,
     pub started: Option<NaiveDateTime>,
+    pub defeated: Vec<Boss>,
+    pub pauses: Vec<Pause>,
+    pub reminders: Vec<NaiveDateTime>,
+    pub last_activity: NaiveDateTime,
+    pub stale_notified: Option<NaiveDateTime>,
+    /// The guild the playthrough was created in
+    pub guild: Option<GuildId>,
+    /// The channel or thread playthrough events are announced in
+    pub channel: Option<ChannelId>,
+    /// The role given to every player, along with the guild it was created in
+    pub role: Option<(GuildId, RoleId)>,
+    pub settings: PlaythroughSettings,
+    /// The playthrough's post on the looking-for-group board, if it is public
+    pub listing: Option<Listing>,
+    /// Every stage the playthrough has been on since it started, along with when it reached that stage
+    pub stage_history: Vec<(Stage, NaiveDateTime)>,
+    /// The vote to progress that is currently open, if any
+    pub vote: Option<ProgressVote>,
+    /// The message showing the playthrough's dashboard, which is kept up to date as the playthrough changes
+    pub dashboard: Option<(ChannelId, MessageId)>,
+}
+
+impl Playthrough {
+    /// The furthest stage this playthrough can be on, starting from its current stage, given the bosses it has defeated
+    pub fn stage_from_bosses(&self) -> Stage {
+        self.stage.furthest(&self.defeated, true)
+    }
+
+    async fn record_activity(&mut self, pool: &PgPool) {
+        let now = Utc::now().naive_utc();
+        sqlx::query("UPDATE playthroughs SET last_activity = $1, stale_notified = NULL WHERE owner = $2")
+            .bind(now)
+            .bind(BigDecimal::from(self.owner.get()))
+            .execute(pool).await.expect("query works");
+
+        self.last_activity = now;
+        self.stale_notified = None;
+    }
+
+    async fn record_stage(&mut self, pool: &PgPool) {
+        let Some(started) = self.started else { return };
+        let now = Utc::now().naive_utc().max(started);
+        sqlx::query("INSERT INTO playthrough_stages(playthrough_owner, stage, reached_at) VALUES ($1, $2, $3)")
+            .bind(BigDecimal::from(self.owner.get()))
+            .bind(self.stage as i16)
+            .bind(now)
+            .execute(pool).await.expect("query works");
+
+        self.stage_history.push((self.stage, now));
+    }
+
+    /// Clears the stage of every player that the playthrough has caught up to after moving from `old_stage`, which are players on
+    /// the new stage and players that were ahead but no longer are. Players that chose to stay behind keep their stage
+    async fn catch_up_players(&mut self, old_stage: Stage, pool: &PgPool) {
+        sqlx::query("UPDATE playthrough_players SET stage = NULL WHERE playthrough_owner = $1 AND (stage = $2 OR (stage > $3 AND stage <= $2))")
+            .bind(BigDecimal::from(self.owner.get()))
+            .bind(self.stage as i16)
+            .bind(old_stage as i16)
+            .execute(pool).await.expect("query works");
+
+        let (old_stage, new_stage) = (old_stage as usize, self.stage as usize);
+        self.players.iter_mut()
+            .filter(|player| player.stage.is_some_and(|stage| {
+                let stage = stage as usize;
+                stage == new_stage || (stage > old_stage && stage <= new_stage)
+            }))
+            .for_each(|player| player.stage = None);
+    }
+
+    /// Closes the open vote, if there is one, since the stage it would progress to is out of date once the playthrough has moved
+    async fn cancel_vote(&mut self, pool: &PgPool) -> Option<ProgressVote> {
+        let vote = self.vote.take()?;
+
+        sqlx::query("DELETE FROM progress_votes WHERE playthrough_owner = $1")
+            .bind(BigDecimal::from(self.owner.get()))
+            .execute(pool).await.expect("query works");
+
+        Some(vote)
+    }
+
+    /// The percentage of players that have to agree to progress, if a new vote can be opened
+    pub fn vote_majority(&self) -> Result<u8, StartVoteError> {
+        if self.vote.is_some() { return Err(StartVoteError::VoteInProgress) }
+        self.settings.vote_majority.ok_or(StartVoteError::VotingDisabled)
+    }
+
+    /// The stage `player` is on, which is the playthrough's stage unless they have progressed on their own
+    pub fn stage_of(&self, player: &Player) -> Stage {
+        player.stage.unwrap_or(self.stage)
+    }
+
+    /// The active time spent on each stage up until `now`, in the order the stages were reached. Time spent paused isn't counted
+    pub fn stage_times(&self, now: NaiveDateTime) -> Vec<(Stage, TimeDelta)> {
+        let Some(started) = self.started else { return Vec::new() };
+        // playthroughs started before stages were tracked only know their current stage
+        let history = if self.stage_history.is_empty() { vec![(self.stage, started)] } else { self.stage_history.clone() };
+
+        let mut times: Vec<(Stage, TimeDelta)> = Vec::new();
+        for (i, (stage, reached_at)) in history.iter().enumerate() {
+            let left_at = history.get(i + 1).map(|(_, reached_at)| *reached_at).unwrap_or(now);
+            let paused = self.pauses.iter()
+                .map(|pause| (pause.paused.max(*reached_at), pause.resumed.unwrap_or(now).min(left_at)))
+                .filter(|(paused, resumed)| resumed > paused)
+                .fold(TimeDelta::zero(), |total, (paused, resumed)| total + (resumed - paused));
+            let time = left_at - *reached_at - paused;
+
+            match times.iter_mut().find(|(time_stage, _)| time_stage == stage) {
+                Some((_, total)) => *total += time,
+                None => times.push((*stage, time)),
+            }
+        }
+        times
+    }
+
+    /// Whether a new player can join as `class`
+    pub fn class_available(&self, class: CalamityClass) -> bool {
+        !self.settings.unique_classes || !self.players.iter().any(|player| player.class == class)
+    }
+
+    /// How many players are playing each class
+    pub fn class_coverage(&self) -> Vec<(CalamityClass, usize)> {
+        CalamityClass::all()
+            .map(|class| (class, self.players.iter().filter(|player| player.class == class).count()))
+            .collect()
+    }
+
+    /// The classes the playthrough is looking for that nobody is playing yet
+    pub fn missing_classes(&self) -> Vec<CalamityClass> {
+        let Some(listing) = &self.listing else { return Vec::new() };
+        listing.wanted_classes.iter()
+            .filter(|class| !self.players.iter().any(|player| player.class == **class))
+            .copied()
+            .collect()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.pauses.last().is_some_and(|pause| pause.resumed.is_none())
+    }
+
+    /// The amount of time spent playing up until `now`, excluding the time this playthrough was paused
+    pub fn active_time(&self, now: NaiveDateTime) -> Option<TimeDelta> {
+        let paused = self.pauses.iter()
+            .map(|pause| pause.resumed.unwrap_or(now) - pause.paused)
+            .fold(TimeDelta::zero(), |total, paused| total + paused);
+        self.started.map(|started| now - started - paused)
+    }
+}
+
+#[derive(Clone, Default, Serialize)]
+pub struct PlaythroughSettings {
+    pub difficulty: Option<Difficulty>,
+    pub world_name: Option<String>,
+    pub world_size: Option<WorldSize>,
+    pub seed: Option<String>,
+    pub evil: Option<WorldEvil>,
+    /// The internal names of the tModLoader mods that are enabled
+    pub mods: Vec<String>,
+    pub notes: Option<String>,
+    /// Prevents more than one player from playing the same class
+    pub unique_classes: bool,
+    /// The percentage of players that have to agree for a vote to progress to pass. If `None`, only the owner can progress
+    pub vote_majority: Option<u8>,
+}
+
+impl PlaythroughSettings {
+    /// Resets a setting back to not being set
+    pub fn clear(&mut self, setting: Setting) {
+        match setting {
+            Setting::Difficulty => self.difficulty = None,
+            Setting::WorldName => self.world_name = None,
+            Setting::WorldSize => self.world_size = None,
+            Setting::Seed => self.seed = None,
+            Setting::Evil => self.evil = None,
+            Setting::Mods => self.mods.clear(),
+            Setting::Notes => self.notes = None,
+        }
+    }
+}
+
+/// The playthrough settings that can be cleared
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ChoiceParameter)]
+pub enum Setting {
+    Difficulty,
+    #[name = "World Name"]
+    WorldName,
+    #[name = "World Size"]
+    WorldSize,
+    Seed,
+    #[name = "World Evil"]
+    Evil,
+    Mods,
+    Notes,
+}
+
+#[derive(Clone, Serialize)]
+pub struct Listing {
+    /// The owner's name when the playthrough was listed, since the website can't look it up
+    pub owner_name: String,
+    pub description: String,
+    /// The classes the owner is looking for. If empty, players of any class can join
+    pub wanted_classes: Vec<CalamityClass>,
+    pub listed_at: NaiveDateTime,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq, ChoiceParameter, FromPrimitive)]
+pub enum Difficulty {
+    Classic,
+    Expert,
+    Revengeance,
+    Death,
+    #[name = "Master Revengeance"]
+    MasterRevengeance,
+    #[name = "Master Death"]
+    MasterDeath,
+    Journey,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq, ChoiceParameter, FromPrimitive)]
+pub enum WorldSize {
+    Small,
+    Medium,
+    Large,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq, ChoiceParameter, FromPrimitive)]
+pub enum WorldEvil {
+    Corruption,
+    Crimson,
+}
+
+#[derive(Clone, Serialize)]
+pub struct Pause {
+    pub paused: NaiveDateTime,
+    pub resumed: Option<NaiveDateTime>,
 }
 
 #[derive(Clone, Serialize)]
 pub struct Player {
     pub user_id: UserId,
     pub class: CalamityClass,
+    /// The stage this player is on, if they have moved ahead of or fallen behind the rest of the playthrough
+    pub stage: Option<Stage>,
 }
 
 impl From<RawPlayer> for Player {
@@ -261,6 +1156,7 @@ impl From<&RawPlayer> for Player {
         Self {
             user_id: UserId::new(value.0.to_u64().expect("user id is a valid u64")),
             class: FromPrimitive::from_i16(value.2).expect("class id is a valid class"),
+            stage: value.3.map(|stage| FromPrimitive::from_i16(stage).expect("stage id is a valid stage")),
         }
     }
 }
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use num_derive::FromPrimitive;
+use num_traits::{FromPrimitive, ToPrimitive};
+use poise::{ChoiceParameter, serenity_prelude::UserId};
+use sqlx::{PgPool, types::BigDecimal};
+use tracing::info;
+
+use crate::loadout_data::CalamityClass;
+
+type RawPreferences = (BigDecimal, i16, bool, Vec<i16>, bool, bool);
+
+/// Where a player's loadouts are sent when their playthrough starts or progresses
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ChoiceParameter, FromPrimitive)]
+pub enum Delivery {
+    #[name = "Direct Message"]
+    #[default]
+    DirectMessage,
+    #[name = "Playthrough Channel"]
+    Channel,
+    #[name = "Off"]
+    Off,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct UserPreferences {
+    pub delivery: Delivery,
+    /// Sends loadouts as plain text instead of embeds
+    pub compact: bool,
+    /// Classes to also send loadouts for, on top of the class the player is playing
+    pub extra_classes: Vec<CalamityClass>,
+    /// Stops the bot from messaging the player when an issue they reported is handled
+    pub mute_issue_updates: bool,
+    /// Shows the player's name on the issues they reported on the public issue tracker
+    pub public_reports: bool,
+}
+
+#[derive(Default)]
+pub struct PreferenceData {
+    preferences: HashMap<UserId, UserPreferences>,
+}
+
+impl PreferenceData {
+    pub fn get(&self, user: UserId) -> UserPreferences {
+        self.preferences.get(&user).cloned().unwrap_or_default()
+    }
+
+    pub async fn update(&mut self, user: UserId, pool: &PgPool, update: impl FnOnce(&mut UserPreferences)) -> &UserPreferences {
+        let preferences = self.preferences.entry(user).or_default();
+        update(preferences);
+
+        sqlx::query("INSERT INTO user_preferences(user_id, delivery, compact, extra_classes, mute_issue_updates, public_reports)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (user_id) DO UPDATE SET delivery = $2, compact = $3, extra_classes = $4, mute_issue_updates = $5, public_reports = $6")
+            .bind(BigDecimal::from(user.get()))
+            .bind(preferences.delivery as i16)
+            .bind(preferences.compact)
+            .bind(preferences.extra_classes.iter().map(|class| *class as i16).collect::<Vec<_>>())
+            .bind(preferences.mute_issue_updates)
+            .bind(preferences.public_reports)
+            .execute(pool).await.expect("query works");
+
+        info!("updated preferences of {}", user);
+
+        preferences
+    }
+
+    pub async fn load(pool: &PgPool) -> PreferenceData {
+        let raw: Vec<RawPreferences> = sqlx::query_as("SELECT user_id, delivery, compact, extra_classes, mute_issue_updates, public_reports
+                FROM user_preferences")
+            .fetch_all(pool).await.expect("query works");
+
+        let preferences = raw.into_iter()
+            .map(|(user_id, delivery, compact, extra_classes, mute_issue_updates, public_reports)| {
+                let user_id = UserId::new(user_id.to_u64().expect("user snowflake is a valid u64"));
+                let preferences = UserPreferences {
+                    delivery: Delivery::from_i16(delivery).expect("delivery is valid"),
+                    compact,
+                    extra_classes: extra_classes.into_iter().map(|class| CalamityClass::from_i16(class).expect("class is valid")).collect(),
+                    mute_issue_updates,
+                    public_reports,
+                };
+                (user_id, preferences)
+            })
+            .collect();
+
+        PreferenceData { preferences }
+    }
+}
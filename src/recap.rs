@@ -0,0 +1,132 @@
+use poise::{ChoiceParameter, serenity_prelude::{Color, CreateEmbed, CreateEmbedFooter, Timestamp, UserId}};
+use reqwest::Url;
+use rand::{distributions::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, types::{BigDecimal, chrono::NaiveDateTime}};
+
+use crate::{boss::Boss, bulleted, format_duration, loadout_data::{CalamityClass, Stage}, playthrough_data::Playthrough, str, TimeDelta};
+
+/// The length of the tokens recap pages are found by, which are random so that recaps can't be looked through one by one
+const TOKEN_LENGTH: usize = 16;
+
+/// A summary of a finished playthrough. Recaps are stored as JSON so they can be viewed after the playthrough is gone
+#[derive(Serialize, Deserialize)]
+pub struct Recap {
+    pub owner: UserId,
+    pub owner_name: String,
+    pub players: Vec<RecapPlayer>,
+    pub final_stage: Stage,
+    pub started: Option<NaiveDateTime>,
+    pub ended: NaiveDateTime,
+    pub active_seconds: Option<i64>,
+    pub stages: Vec<RecapStage>,
+    pub defeated: Vec<Boss>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RecapPlayer {
+    pub name: String,
+    pub class: CalamityClass,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RecapStage {
+    pub stage: Stage,
+    pub seconds: i64,
+}
+
+impl Recap {
+    /// Creates a recap of a playthrough that ended at `ended`. `player_names` are the names of the playthrough's players, in order
+    pub fn new(playthrough: &Playthrough, player_names: Vec<String>, ended: NaiveDateTime) -> Recap {
+        let players: Vec<RecapPlayer> = playthrough.players.iter().zip(player_names)
+            .map(|(player, name)| RecapPlayer { name, class: player.class })
+            .collect();
+        let owner_name = players.iter().zip(&playthrough.players)
+            .find(|(_, player)| player.user_id == playthrough.owner)
+            .map(|(recap_player, _)| recap_player.name.clone())
+            .unwrap_or_default();
+
+        Recap {
+            owner: playthrough.owner,
+            owner_name,
+            players,
+            final_stage: playthrough.stage,
+            started: playthrough.started,
+            ended,
+            active_seconds: playthrough.active_time(ended).map(|active_time| active_time.num_seconds()),
+            stages: playthrough.stage_times(ended).into_iter()
+                .map(|(stage, time)| RecapStage { stage, seconds: time.num_seconds() })
+                .collect(),
+            defeated: playthrough.defeated.clone(),
+        }
+    }
+
+    /// The stages that took the least and most time, if more than one stage was played
+    pub fn fastest_and_slowest(&self) -> Option<(&RecapStage, &RecapStage)> {
+        if self.stages.len() < 2 { return None }
+        let fastest = self.stages.iter().min_by_key(|stage| stage.seconds)?;
+        let slowest = self.stages.iter().max_by_key(|stage| stage.seconds)?;
+        Some((fastest, slowest))
+    }
+
+    pub fn create_embed(&self, token: &str) -> CreateEmbed {
+        let players = self.players.iter().map(|player| format!("{} - {}{}", player.name, player.class.name(), player.class.emoji()));
+        let stages = self.stages.iter().map(|stage| format!("{}: {}", stage.stage.name(), format_seconds(stage.seconds)));
+        let dates = match self.started {
+            Some(started) => format!("<t:{}:D> - <t:{}:D>", started.and_utc().timestamp(), self.ended.and_utc().timestamp()),
+            None => str!("Never started"),
+        };
+
+        let mut embed = CreateEmbed::new()
+            .title(format!("{}'s Playthrough Recap", self.owner_name))
+            .url(Recap::url(token))
+            .thumbnail(self.final_stage.img())
+            .field("Players", bulleted(players), false)
+            .field("Final Stage", self.final_stage.name(), true)
+            .field("Dates", dates, true)
+            .field("Active Time", self.active_seconds.map(format_seconds).unwrap_or(str!("Never started")), true)
+            .field("Bosses Defeated", self.defeated.len().to_string(), true);
+        if !self.stages.is_empty() {
+            embed = embed.field("Time per Stage", bulleted(stages), false);
+        }
+        if let Some((fastest, slowest)) = self.fastest_and_slowest() {
+            embed = embed
+                .field("Fastest Stage", format!("{} ({})", fastest.stage.name(), format_seconds(fastest.seconds)), true)
+                .field("Slowest Stage", format!("{} ({})", slowest.stage.name(), format_seconds(slowest.seconds)), true);
+        }
+
+        embed
+            .color(Color::GOLD)
+            .footer(CreateEmbedFooter::new("Loadouts by GitGudWO").icon_url(crate::get_asset("gitgudpfp.jpg")))
+            .timestamp(Timestamp::now())
+    }
+
+    /// Stores the recap, returning the random token its page can be found with
+    pub async fn save(&self, pool: &PgPool) -> String {
+        let token: String = rand::thread_rng().sample_iter(Alphanumeric).take(TOKEN_LENGTH).map(char::from).collect();
+        sqlx::query("INSERT INTO playthrough_recaps(owner, token, data) VALUES ($1, $2, $3)")
+            .bind(BigDecimal::from(self.owner.get()))
+            .bind(&token)
+            .bind(serde_json::to_string(self).expect("recap serializes"))
+            .execute(pool).await.expect("query works");
+        token
+    }
+
+    pub async fn load(token: &str, pool: &PgPool) -> Option<Recap> {
+        let (data,): (String,) = sqlx::query_as("SELECT data FROM playthrough_recaps WHERE token = $1")
+            .bind(token)
+            .fetch_optional(pool).await.expect("query works")?;
+        serde_json::from_str(&data).ok()
+    }
+
+    /// The link to the recap's web page
+    pub fn url(token: &str) -> Url {
+        let mut url = crate::url().join("recap/").expect("path is valid");
+        url.query_pairs_mut().append_pair("id", token);
+        url
+    }
+}
+
+fn format_seconds(seconds: i64) -> String {
+    format_duration(TimeDelta::seconds(seconds))
+}
@@ -0,0 +1,48 @@
+use futures::future::join_all;
+use poise::serenity_prelude::{CacheHttp, EditRole, Error, GuildId, RoleId, UserId};
+use tracing::warn;
+
+use crate::playthrough_data::Playthrough;
+
+/// Creates a mentionable role for a playthrough in `guild` and gives it to every player in the playthrough
+pub async fn create(http: impl CacheHttp, guild: GuildId, owner_name: &str, playthrough: &Playthrough) -> Result<RoleId, Error> {
+    let role = guild.create_role(&http, EditRole::new()
+        .name(format!("{owner_name}'s Calamity Run"))
+        .mentionable(true)
+        .audit_log_reason("playthrough role created")
+    ).await?;
+
+    join_all(playthrough.players.iter()
+        .map(|player| add(&http, guild, role.id, player.user_id))).await;
+
+    Ok(role.id)
+}
+
+/// Gives the playthrough's role (if it has one) to a player who joined
+pub async fn add_player(http: impl CacheHttp, playthrough: &Playthrough, player: UserId) {
+    let Some((guild, role)) = playthrough.role else { return };
+    add(http, guild, role, player).await;
+}
+
+/// Removes the playthrough's role (if it has one) from a player who left or was kicked
+pub async fn remove_player(http: impl CacheHttp, playthrough: &Playthrough, player: UserId) {
+    let Some((guild, role)) = playthrough.role else { return };
+    if let Err(err) = http.http().remove_member_role(guild, player, role, Some("player left playthrough")).await {
+        warn!("could not remove role {role} from {player}: {err}");
+    }
+}
+
+/// Deletes the playthrough's role, if it has one
+pub async fn delete(http: impl CacheHttp, playthrough: &Playthrough) {
+    let Some((guild, role)) = playthrough.role else { return };
+    if let Err(err) = guild.delete_role(http.http(), role).await {
+        warn!("could not delete playthrough role {role}: {err}");
+    }
+}
+
+async fn add(http: impl CacheHttp, guild: GuildId, role: RoleId, player: UserId) {
+    // players that aren't members of the guild can't be given the role, which is fine
+    if let Err(err) = http.http().add_member_role(guild, player, role, Some("player joined playthrough")).await {
+        warn!("could not add role {role} to {player}: {err}");
+    }
+}
@@ -0,0 +1,125 @@
+use std::{sync::Arc, time::Duration};
+
+use futures::future::join_all;
+use poise::serenity_prelude::{Color, CreateEmbed, CreateMessage, EditMessage, Http, Timestamp, UserId};
+use sqlx::{PgPool, types::chrono::Utc};
+use tokio::sync::RwLock;
+use tracing::warn;
+
+use crate::{announce::{self, PlaythroughEvent}, commands::playthrough::close_dashboard, format_duration, playthrough_data::PlaythroughData, roles, vote::VoteOutcome, TimeDelta};
+
+const TICK: Duration = Duration::from_secs(60);
+
+/// Background task that sends playthrough reminders, closes expired votes and cleans up abandoned playthroughs
+pub struct Scheduler {
+    pub http: Arc<Http>,
+    pub pool: PgPool,
+    pub playthroughs: Arc<RwLock<PlaythroughData>>,
+    /// How long a playthrough can go without any progress before its owner is asked about it
+    pub stale_after: TimeDelta,
+    /// How long after the owner was asked that the playthrough is archived
+    pub archive_after: TimeDelta,
+    /// How long a paused playthrough can go without any progress before it is archived. Paused playthroughs aren't asked about first
+    pub archive_paused_after: TimeDelta,
+}
+
+impl Scheduler {
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(TICK);
+        loop {
+            interval.tick().await;
+            self.send_reminders().await;
+            self.check_stale().await;
+            self.expire_votes().await;
+        }
+    }
+
+    async fn send_reminders(&self) {
+        let now = Utc::now().naive_utc();
+        let mut playthroughs = self.playthroughs.write().await;
+        let owners = playthroughs.take_due_reminders(now, &self.pool).await;
+
+        let players: Vec<UserId> = owners.iter()
+            .filter_map(|owner| playthroughs.active_playthroughs.get(owner))
+            .flat_map(|playthrough| playthrough.players.iter().map(|player| player.user_id))
+            .collect();
+        drop(playthroughs);
+
+        join_all(players.into_iter().map(|player| self.dm(player, CreateEmbed::new()
+            .title("Playthrough Reminder")
+            .description("It's time for your playthrough session!")
+            .color(Color::FOOYOO)
+        ))).await;
+    }
+
+    async fn check_stale(&self) {
+        let now = Utc::now().naive_utc();
+        let mut playthroughs = self.playthroughs.write().await;
+
+        let (mut to_archive, to_notify): (Vec<_>, Vec<_>) = playthroughs.active_playthroughs.values()
+            .filter(|playthrough| !playthrough.is_paused())
+            .filter(|playthrough| now - playthrough.last_activity >= self.stale_after)
+            .filter(|playthrough| playthrough.stale_notified.is_none_or(|notified| now - notified >= self.archive_after))
+            .map(|playthrough| (playthrough.owner, playthrough.stale_notified.is_some()))
+            .partition(|(_, notified)| *notified);
+        to_archive.extend(playthroughs.active_playthroughs.values()
+            .filter(|playthrough| playthrough.is_paused() && now - playthrough.last_activity >= self.archive_paused_after)
+            .map(|playthrough| (playthrough.owner, true)));
+
+        for (owner, _) in &to_notify {
+            playthroughs.mark_stale_notified(*owner, &self.pool).await;
+        }
+        let mut archived = Vec::new();
+        for (owner, _) in to_archive {
+            archived.extend(playthroughs.archive(owner, &self.pool).await);
+        }
+        drop(playthroughs);
+
+        for (owner, _) in to_notify {
+            self.dm(owner, CreateEmbed::new()
+                .title("Is your playthrough still going?")
+                .description(format!(
+                    "Your playthrough hasn't made any progress in {}. If nothing happens in the next {}, it will be archived.",
+                    format_duration(self.stale_after),
+                    format_duration(self.archive_after),
+                ))
+                .color(Color::ORANGE)
+            ).await;
+        }
+
+        for playthrough in archived {
+            announce::announce(&self.http, &playthrough, PlaythroughEvent::Archived).await;
+            roles::delete(&self.http, &playthrough).await;
+            close_dashboard(&self.http, &playthrough).await;
+            self.dm(playthrough.owner, CreateEmbed::new()
+                .title("Playthrough Archived")
+                .description("Your playthrough was archived since it was abandoned. Feel free to create a new one at any time!")
+                .color(Color::RED)
+            ).await;
+        }
+    }
+
+    /// Closes the polls of votes that expired or were cancelled since the last tick
+    async fn expire_votes(&self) {
+        let now = Utc::now().naive_utc();
+        let (expired, cancelled) = {
+            let mut playthroughs = self.playthroughs.write().await;
+            (playthroughs.take_expired_votes(now, &self.pool).await, playthroughs.take_cancelled_votes())
+        };
+
+        let votes = expired.into_iter().map(|vote| (vote, VoteOutcome::Expired))
+            .chain(cancelled.into_iter().map(|vote| (vote, VoteOutcome::Cancelled)));
+        for ((owner, vote), outcome) in votes {
+            let message = EditMessage::new().embed(vote.create_embed(owner, outcome)).components(Vec::new());
+            if let Err(err) = vote.channel.edit_message(&self.http, vote.message, message).await {
+                warn!("could not close vote in {}: {err}", vote.channel);
+            }
+        }
+    }
+
+    async fn dm(&self, user: UserId, embed: CreateEmbed) {
+        if let Err(err) = user.direct_message(&self.http, CreateMessage::new().embed(embed.timestamp(Timestamp::now()))).await {
+            warn!("could not DM {user}: {err}");
+        }
+    }
+}
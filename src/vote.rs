@@ -0,0 +1,79 @@
+use poise::{ChoiceParameter, serenity_prelude::{ButtonStyle, ChannelId, Color, CreateActionRow, CreateButton, CreateEmbed, MessageId, Timestamp, UserId}};
+use serde::Serialize;
+use sqlx::types::chrono::NaiveDateTime;
+
+use crate::{loadout_data::Stage, playthrough_data::Player, str, TimeDelta};
+
+/// How long players have to vote before a vote expires
+pub fn vote_duration() -> TimeDelta {
+    TimeDelta::hours(24)
+}
+
+/// A poll among a playthrough's players on whether to progress to `stage`
+#[derive(Clone, Serialize)]
+pub struct ProgressVote {
+    pub stage: Stage,
+    pub started_by: UserId,
+    /// Where the poll was posted
+    pub channel: ChannelId,
+    pub message: MessageId,
+    pub expires_at: NaiveDateTime,
+    /// Every player that has voted, and whether they voted to progress
+    pub ballots: Vec<(UserId, bool)>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum VoteOutcome {
+    Pending { approvals: usize, needed: usize },
+    Passed,
+    Failed,
+    Expired,
+    /// The playthrough moved on before the vote ended
+    Cancelled,
+}
+
+impl ProgressVote {
+    /// Counts the ballots of the current players. `majority` is the percentage of players that have to vote to progress
+    pub fn tally(&self, players: &[Player], majority: u8) -> VoteOutcome {
+        let count = |approve: bool| self.ballots.iter()
+            .filter(|(voter, vote)| *vote == approve && players.iter().any(|player| player.user_id == *voter))
+            .count();
+        let (approvals, rejections) = (count(true), count(false));
+        let needed = (players.len() * majority as usize).div_ceil(100).max(1);
+
+        if approvals >= needed {
+            VoteOutcome::Passed
+        } else if players.len() - rejections < needed {
+            VoteOutcome::Failed
+        } else {
+            VoteOutcome::Pending { approvals, needed }
+        }
+    }
+
+    pub fn create_embed(&self, owner: UserId, outcome: VoteOutcome) -> CreateEmbed {
+        let (status, color) = match outcome {
+            VoteOutcome::Pending { approvals, needed } => (
+                format!("{approvals}/{needed} votes to progress. The vote ends <t:{}:R>", self.expires_at.and_utc().timestamp()),
+                Color::BLUE,
+            ),
+            VoteOutcome::Passed => (format!("The vote passed! The playthrough progressed to `{}`", self.stage.name()), Color::FOOYOO),
+            VoteOutcome::Failed => (str!("The vote failed"), Color::RED),
+            VoteOutcome::Expired => (str!("The vote expired before enough players voted"), Color::DARK_GREY),
+            VoteOutcome::Cancelled => (str!("The vote was cancelled since the playthrough moved on"), Color::DARK_GREY),
+        };
+
+        CreateEmbed::new()
+            .title("Vote to Progress")
+            .description(format!("<@{}> wants to progress <@{owner}>'s playthrough to `{}`\n\n{status}", self.started_by, self.stage.name()))
+            .thumbnail(self.stage.img())
+            .color(color)
+            .timestamp(Timestamp::now())
+    }
+
+    pub fn create_buttons(owner: UserId) -> Vec<CreateActionRow> {
+        vec![CreateActionRow::Buttons(vec![
+            CreateButton::new(format!("vote-yes-{owner}")).style(ButtonStyle::Success).label("Progress"),
+            CreateButton::new(format!("vote-no-{owner}")).style(ButtonStyle::Danger).label("Not yet"),
+        ])]
+    }
+}
@@ -4,17 +4,33 @@ use axum::{routing::get, Router};
 use tokio::sync::RwLock;
 use tower_http::services::ServeDir;
 
-use crate::{loadout_data::LoadoutData, playthrough_data::PlaythroughData};
+use sqlx::PgPool;
+
+use crate::{issue::Issues, loadout_data::LoadoutData, playthrough_data::PlaythroughData, preferences::PreferenceData};
 
 pub mod route;
 
-pub fn app(loadouts: Arc<RwLock<LoadoutData>>, playthroughs: Arc<RwLock<PlaythroughData>>) -> Router {
+pub fn app(
+    loadouts: Arc<RwLock<LoadoutData>>,
+    playthroughs: Arc<RwLock<PlaythroughData>>,
+    issues: Arc<RwLock<Issues>>,
+    preferences: Arc<RwLock<PreferenceData>>,
+    pool: PgPool,
+) -> Router {
     Router::new()
         .nest_service("/", ServeDir::new("static/public"))
         .route("/invite", get(route::invite))
         .route("/api/loadout/:class/:stage", get(route::loadout))
         .with_state(loadouts)
         .route("/api/playthrough/:owner", get(route::playthrough))
+        .route("/api/lfg", get(route::lfg))
         .with_state(playthroughs)
+        .route("/api/issues", get(route::issues))
+        .route("/api/issues/:id", get(route::issue))
+        .with_state((issues, preferences))
+        .route("/api/changelog", get(route::changelog))
+        .route("/api/changelog.atom", get(route::changelog_atom))
+        .route("/api/recap/:id", get(route::recap))
+        .with_state(pool)
 }
 
@@ -1,13 +1,27 @@
-use std::sync::Arc;
+use std::{cmp::Reverse, sync::Arc};
 
-use axum::{extract::{Path, State}, http::StatusCode, response::{IntoResponse, Redirect, Response}, Json};
+use axum::{extract::{Path, State}, http::{header, StatusCode}, response::{IntoResponse, Redirect, Response}, Json};
 use linked_hash_map::LinkedHashMap;
-use poise::serenity_prelude::UserId;
+use poise::{serenity_prelude::UserId, ChoiceParameter};
 use reqwest::Url;
 use serde::Serialize;
 use tokio::sync::RwLock;
 
-use crate::{loadout_data::{CalamityClass, Loadout, LoadoutData, Stage, StageData}, playthrough_data::PlaythroughData};
+use sqlx::PgPool;
+
+use crate::{
+    changelog::{self, ChangelogEntry},
+    issue::{Issue, Issues},
+    loadout_data::{CalamityClass, Loadout, LoadoutData, Stage, StageData},
+    playthrough_data::{Listing, Playthrough, PlaythroughData},
+    preferences::PreferenceData,
+    recap::Recap,
+};
+
+/// How many of the latest loadout changes are in the changelog
+const CHANGELOG_LENGTH: i64 = 50;
+
+type IssueState = (Arc<RwLock<Issues>>, Arc<RwLock<PreferenceData>>);
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 struct ApiLoadout<'a> {
@@ -42,8 +56,200 @@ impl<'a> ApiLoadout<'a> {
     }
 }
 
+#[derive(Debug, Clone, Serialize)]
+struct ApiListing<'a> {
+    pub owner: UserId,
+    pub owner_name: &'a str,
+    pub description: &'a str,
+    pub stage: String,
+    pub stage_img: Url,
+    pub difficulty: Option<&'static str>,
+    pub players: Vec<String>,
+    /// If true, players of any class can join and `wanted_classes` is empty
+    pub any_class: bool,
+    pub wanted_classes: Vec<String>,
+    pub listed_at: i64,
+}
+
+impl<'a> ApiListing<'a> {
+    pub fn new(playthrough: &'a Playthrough, Listing { owner_name, description, wanted_classes, listed_at }: &'a Listing) -> Self {
+        Self {
+            owner: playthrough.owner,
+            owner_name,
+            description,
+            stage: playthrough.stage.to_string(),
+            stage_img: playthrough.stage.img(),
+            difficulty: playthrough.settings.difficulty.map(|difficulty| difficulty.name()),
+            players: playthrough.players.iter().map(|player| player.class.to_string()).collect(),
+            any_class: wanted_classes.is_empty(),
+            wanted_classes: playthrough.missing_classes().iter().map(ToString::to_string).collect(),
+            listed_at: listed_at.and_utc().timestamp(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ApiPlayer {
+    pub user_id: UserId,
+    pub class: String,
+    pub stage: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ApiPlaythrough<'a> {
+    pub owner: UserId,
+    pub players: Vec<ApiPlayer>,
+    pub stage: String,
+    pub stage_img: Url,
+    pub started: Option<i64>,
+    pub paused: bool,
+    pub defeated: Vec<&'static str>,
+    pub difficulty: Option<&'static str>,
+    pub world_size: Option<&'static str>,
+    pub evil: Option<&'static str>,
+    pub mods: &'a [String],
+}
+
+impl<'a> ApiPlaythrough<'a> {
+    pub fn new(playthrough: &'a Playthrough) -> Self {
+        let settings = &playthrough.settings;
+        Self {
+            owner: playthrough.owner,
+            players: playthrough.players.iter().map(|player| ApiPlayer {
+                user_id: player.user_id,
+                class: player.class.to_string(),
+                stage: playthrough.stage_of(player).to_string(),
+            }).collect(),
+            stage: playthrough.stage.to_string(),
+            stage_img: playthrough.stage.img(),
+            started: playthrough.started.map(|started| started.and_utc().timestamp()),
+            paused: playthrough.is_paused(),
+            defeated: playthrough.defeated.iter().map(|boss| boss.name()).collect(),
+            difficulty: settings.difficulty.map(|difficulty| difficulty.name()),
+            world_size: settings.world_size.map(|world_size| world_size.name()),
+            evil: settings.evil.map(|evil| evil.name()),
+            mods: &settings.mods,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ApiRecap<'a> {
+    pub owner_name: &'a str,
+    pub players: Vec<(&'a str, String)>,
+    pub final_stage: String,
+    pub final_stage_img: Url,
+    pub started: Option<i64>,
+    pub ended: i64,
+    pub active_seconds: Option<i64>,
+    pub stages: Vec<(String, i64)>,
+    pub fastest: Option<String>,
+    pub slowest: Option<String>,
+    pub defeated: Vec<&'static str>,
+}
+
+impl<'a> ApiRecap<'a> {
+    pub fn new(recap: &'a Recap) -> Self {
+        let (fastest, slowest) = recap.fastest_and_slowest().unzip();
+        Self {
+            owner_name: &recap.owner_name,
+            players: recap.players.iter().map(|player| (&player.name[..], player.class.to_string())).collect(),
+            final_stage: recap.final_stage.to_string(),
+            final_stage_img: recap.final_stage.img(),
+            started: recap.started.map(|started| started.and_utc().timestamp()),
+            ended: recap.ended.and_utc().timestamp(),
+            active_seconds: recap.active_seconds,
+            stages: recap.stages.iter().map(|stage| (stage.stage.to_string(), stage.seconds)).collect(),
+            fastest: fastest.map(|stage| stage.stage.to_string()),
+            slowest: slowest.map(|stage| stage.stage.to_string()),
+            defeated: recap.defeated.iter().map(|boss| boss.name()).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ApiIssue<'a> {
+    /// The issue's id in hex, as shown in its embed
+    pub id: String,
+    pub status: &'static str,
+    pub closed: bool,
+    pub class: &'static str,
+    pub stage: String,
+    pub stage_img: Url,
+    pub section: Option<&'a str>,
+    pub incorrect: &'a str,
+    pub correct: &'a str,
+    pub explanation: Option<&'a str>,
+    pub supporters: usize,
+    /// Only shown if the reporter turned on `/preferences public-reports`
+    pub reporter: Option<ApiReporter<'a>>,
+    pub created_at: i64,
+    pub updated_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ApiReporter<'a> {
+    pub id: UserId,
+    pub name: &'a str,
+}
+
+impl<'a> ApiIssue<'a> {
+    pub fn new(issue: &'a Issue, preferences: &PreferenceData) -> Self {
+        let reporter = preferences.get(issue.author).public_reports
+            .then(|| ApiReporter { id: issue.author, name: issue.author_name.as_deref().unwrap_or("Unknown") });
+        Self {
+            id: format!("{:x}", issue.id),
+            status: issue.status.name(),
+            closed: issue.status.is_closed(),
+            class: issue.class_name(),
+            stage: issue.stage.to_string(),
+            stage_img: issue.stage.img(),
+            section: issue.section.as_deref(),
+            incorrect: &issue.incorrect,
+            correct: &issue.correct,
+            explanation: issue.explanation.as_deref(),
+            supporters: issue.supporters.len(),
+            reporter,
+            created_at: issue.created_at.and_utc().timestamp(),
+            updated_at: issue.history.last().map(|change| change.changed_at.and_utc().timestamp()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ApiChangelogEntry<'a> {
+    pub id: i32,
+    /// The id of the issue the change fixed in hex, if the issue still exists
+    pub issue: Option<String>,
+    pub title: String,
+    pub class: Option<String>,
+    pub stage: String,
+    pub section: &'a str,
+    pub before: &'a str,
+    pub after: &'a str,
+    pub url: Url,
+    pub changed_at: i64,
+}
+
+impl<'a> ApiChangelogEntry<'a> {
+    pub fn new(entry: &'a ChangelogEntry) -> Self {
+        Self {
+            id: entry.id,
+            issue: entry.issue.map(|issue| format!("{issue:x}")),
+            title: entry.title(),
+            class: entry.class.map(|class| class.to_string()),
+            stage: entry.stage.to_string(),
+            section: &entry.section,
+            before: &entry.before,
+            after: &entry.after,
+            url: entry.url(),
+            changed_at: entry.changed_at.and_utc().timestamp(),
+        }
+    }
+}
+
 pub async fn invite() -> Redirect {
-    Redirect::to("https://discord.com/api/oauth2/authorize?client_id=1128716845365596273&permissions=274878171136&scope=bot%20applications.commands")
+    Redirect::to("https://discord.com/api/oauth2/authorize?client_id=1128716845365596273&permissions=309506344960&scope=bot%20applications.commands")
 }
 
 pub async fn loadout(Path((class, stage)): Path<(CalamityClass, Stage)>, State(loadouts): State<Arc<RwLock<LoadoutData>>>) -> Response {
@@ -56,7 +262,49 @@ pub async fn loadout(Path((class, stage)): Path<(CalamityClass, Stage)>, State(l
 pub async fn playthrough(Path(id): Path<UserId>, State(playthroughs): State<Arc<RwLock<PlaythroughData>>>) -> Response {
     let playthroughs = playthroughs.read().await;
     playthroughs.active_playthroughs.get(&id)
-        .map(|playthrough| Json(playthrough).into_response())
+        .map(|playthrough| Json(ApiPlaythrough::new(playthrough)).into_response())
         .unwrap_or(StatusCode::NOT_FOUND.into_response())
 }
 
+pub async fn lfg(State(playthroughs): State<Arc<RwLock<PlaythroughData>>>) -> Response {
+    let playthroughs = playthroughs.read().await;
+    let listings: Vec<ApiListing> = playthroughs.listings().into_iter()
+        .filter_map(|playthrough| Some(ApiListing::new(playthrough, playthrough.listing.as_ref()?)))
+        .collect();
+    Json(listings).into_response()
+}
+
+pub async fn recap(Path(token): Path<String>, State(pool): State<PgPool>) -> Response {
+    Recap::load(&token, &pool).await
+        .map(|recap| Json(ApiRecap::new(&recap)).into_response())
+        .unwrap_or(StatusCode::NOT_FOUND.into_response())
+}
+
+pub async fn issues(State((issues, preferences)): State<IssueState>) -> Response {
+    let issues = issues.read().await;
+    let preferences = preferences.read().await;
+    let mut issues: Vec<ApiIssue> = issues.issues.values()
+        .map(|issue| ApiIssue::new(issue, &preferences))
+        .collect();
+    issues.sort_by_key(|issue| Reverse(issue.created_at));
+    Json(issues).into_response()
+}
+
+pub async fn issue(Path(id): Path<String>, State((issues, preferences)): State<IssueState>) -> Response {
+    let issues = issues.read().await;
+    let preferences = preferences.read().await;
+    i32::from_str_radix(&id, 16).ok()
+        .and_then(|id| issues.issues.get(&id))
+        .map(|issue| Json(ApiIssue::new(issue, &preferences)).into_response())
+        .unwrap_or(StatusCode::NOT_FOUND.into_response())
+}
+
+pub async fn changelog(State(pool): State<PgPool>) -> Response {
+    let entries = ChangelogEntry::recent(CHANGELOG_LENGTH, &pool).await;
+    Json(entries.iter().map(ApiChangelogEntry::new).collect::<Vec<_>>()).into_response()
+}
+
+pub async fn changelog_atom(State(pool): State<PgPool>) -> Response {
+    let entries = ChangelogEntry::recent(CHANGELOG_LENGTH, &pool).await;
+    ([(header::CONTENT_TYPE, "application/atom+xml")], changelog::atom_feed(&entries)).into_response()
+}
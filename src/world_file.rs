@@ -0,0 +1,268 @@
+use thiserror::Error;
+
+use crate::{boss::Boss, nbt::{self, NbtError, Tag}};
+
+/// The oldest world version that can be read (Terraria 1.4.0)
+const MIN_VERSION: i32 = 225;
+const MAGIC: &[u8; 7] = b"relogic";
+const WORLD_FILE_TYPE: u8 = 2;
+
+#[derive(Error, Debug)]
+pub enum WorldFileError {
+    #[error("unexpected end of world file")]
+    UnexpectedEof,
+    #[error("this is not a Terraria world file")]
+    InvalidMagic,
+    #[error("world file version {0} is too old, please load the world in Terraria 1.4 first")]
+    UnsupportedVersion(i32),
+    #[error("world file contains an invalid string")]
+    InvalidString,
+    #[error("invalid modded world file: {0}")]
+    Nbt(#[from] NbtError),
+}
+
+#[derive(Debug)]
+pub struct WorldFile {
+    pub name: String,
+    pub seed: String,
+    pub width: i32,
+    pub height: i32,
+    pub game_mode: i32,
+    pub crimson: bool,
+    pub hard_mode: bool,
+    pub defeated: Vec<Boss>,
+}
+
+impl WorldFile {
+    /// Parses the header of a `.wld` file, reading the vanilla boss-downed flags
+    pub fn parse(bytes: &[u8]) -> Result<WorldFile, WorldFileError> {
+        let mut reader = WorldReader { bytes, pos: 0 };
+
+        let version = reader.i32()?;
+        if version < MIN_VERSION { return Err(WorldFileError::UnsupportedVersion(version)) }
+        if reader.take::<7>()? != *MAGIC || reader.u8()? != WORLD_FILE_TYPE { return Err(WorldFileError::InvalidMagic) }
+        reader.skip(4 + 8)?; // revision, favorite flags
+
+        let section_count = reader.i16()?.max(0) as usize;
+        let section_pointers = (0..section_count).map(|_| reader.i32()).collect::<Result<Vec<_>, _>>()?;
+        let header_start = *section_pointers.first().ok_or(WorldFileError::UnexpectedEof)?;
+        reader.seek(header_start.max(0) as usize)?;
+
+        let name = reader.string()?;
+        let seed = reader.string()?;
+        reader.skip(8 + 16 + 4 + 4 * 4)?; // generator version, guid, world id, world bounds
+        let height = reader.i32()?;
+        let width = reader.i32()?;
+        let game_mode = reader.i32()?;
+
+        // drunk world, then getGoodWorld, tenthAnniversaryWorld, dontStarveWorld, notTheBeesWorld, remixWorld, noTrapsWorld and
+        // zenithWorld which were each added in later versions
+        let secret_seeds = 1 + [227, 238, 239, 241, 249, 266, 267].iter().filter(|added| version >= **added).count();
+        reader.skip(secret_seeds)?;
+
+        reader.skip(8 + 1)?; // creation time, moon type
+        reader.skip(4 * (3 + 4 + 3 + 4 + 3 + 2))?; // tree and cave backgrounds, ice/jungle/hell styles, spawn tile
+        reader.skip(8 * 3 + 1 + 4 + 1 + 1 + 4 * 2)?; // world surface, rock layer, time, day time, moon phase, blood moon, eclipse, dungeon
+
+        let crimson = reader.bool()?;
+
+        let mut defeated = Vec::new();
+        for boss in [
+            Some(Boss::EyeOfCthulhu),
+            Some(Boss::EaterOfWorldsOrBrainOfCthulhu),
+            Some(Boss::Skeletron),
+            None, // queen bee
+            Some(Boss::TheDestroyer),
+            Some(Boss::TheTwins),
+            Some(Boss::SkeletronPrime),
+            None, // any mech boss
+            Some(Boss::Plantera),
+            Some(Boss::Golem),
+            Some(Boss::KingSlime),
+        ] {
+            if let (true, Some(boss)) = (reader.bool()?, boss) { defeated.push(boss) }
+        }
+
+        reader.skip(7 + 2 + 1 + 4)?; // saved npcs and invasions, shadow orbs, meteor, orb count, altar count
+        let hard_mode = reader.bool()?;
+        if hard_mode { defeated.push(Boss::WallOfFlesh) }
+        if version >= 257 { reader.skip(1)? } // after party of doom
+
+        reader.skip(4 * 3 + 8 + 8 + 1)?; // invasion delay, size, type and position, slime rain, sundial
+        reader.skip(1 + 4 + 4 + 4 * 3 + 8 + 4)?; // rain, ore tiers, background styles, cloud background
+        reader.skip(2 + 4)?; // cloud count, wind speed
+
+        let anglers = reader.i32()?.max(0);
+        for _ in 0..anglers { reader.string()?; }
+        reader.skip(1 + 4 + 1 + 1 + 1 + 4 + 4)?; // angler, angler quest, stylist, tax collector, golfer, invasion size, cultist delay
+
+        let kill_counts = reader.i16()?.max(0) as usize;
+        reader.skip(4 * kill_counts + 1)?; // kill counts, fast forward time
+
+        reader.bool()?; // duke fishron
+        reader.bool()?; // martian madness
+        if reader.bool()? { defeated.push(Boss::LunaticCultist) }
+        if reader.bool()? { defeated.push(Boss::MoonLord) }
+
+        Ok(WorldFile { name, seed, width, height, game_mode, crimson, hard_mode, defeated })
+    }
+}
+
+impl WorldFile {
+    pub fn game_mode_name(&self) -> &str {
+        match self.game_mode {
+            0 => "Classic",
+            1 => "Expert",
+            2 => "Master",
+            3 => "Journey",
+            _ => "Unknown",
+        }
+    }
+}
+
+/// Reads the Calamity boss-downed flags from a tModLoader `.twld` file
+pub fn parse_modded_flags(bytes: &[u8]) -> Result<Vec<Boss>, WorldFileError> {
+    let root = nbt::read_compressed(bytes)?;
+
+    let mut defeated = Vec::new();
+    root.walk(&mut |key, tag| {
+        if !matches!(key, Some("downedFlags") | Some("downed")) { return }
+        let flags = tag.as_list().unwrap_or_default().iter().filter_map(Tag::as_str);
+        for boss in flags.filter_map(calamity_flag_boss) {
+            if !defeated.contains(&boss) { defeated.push(boss) }
+        }
+    });
+
+    Ok(defeated)
+}
+
+fn calamity_flag_boss(flag: &str) -> Option<Boss> {
+    let boss = match &flag.to_lowercase()[..] {
+        "desertscourge" => Boss::DesertScourge,
+        "crabulon" => Boss::Crabulon,
+        "hivemind" | "perforator" | "perforators" => Boss::HiveMindOrPerforators,
+        "slimegod" => Boss::SlimeGod,
+        "calamitas" | "calamitasclone" => Boss::CalamitasClone,
+        "astrumdeus" => Boss::AstrumDeus,
+        "guardians" | "profanedguardians" => Boss::ProfanedGuardians,
+        "providence" => Boss::Providence,
+        "stormweaver" => Boss::StormWeaver,
+        "ceaselessvoid" => Boss::CeaselessVoid,
+        "signus" => Boss::Signus,
+        "polterghast" => Boss::Polterghast,
+        "dog" | "devourerofgods" => Boss::DevourerOfGods,
+        "yharon" => Boss::Yharon,
+        "exomechs" | "draedon" => Boss::ExoMechs,
+        "supremecalamitas" | "scal" => Boss::SupremeCalamitas,
+        _ => return None,
+    };
+    Some(boss)
+}
+
+struct WorldReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl WorldReader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], WorldFileError> {
+        let bytes = self.bytes.get(self.pos..self.pos + N).ok_or(WorldFileError::UnexpectedEof)?;
+        self.pos += N;
+        Ok(bytes.try_into().expect("slice has length N"))
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), WorldFileError> {
+        self.seek(self.pos + len)
+    }
+
+    fn seek(&mut self, pos: usize) -> Result<(), WorldFileError> {
+        if pos > self.bytes.len() { return Err(WorldFileError::UnexpectedEof) }
+        self.pos = pos;
+        Ok(())
+    }
+
+    fn u8(&mut self) -> Result<u8, WorldFileError> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn bool(&mut self) -> Result<bool, WorldFileError> {
+        Ok(self.u8()? != 0)
+    }
+
+    fn i16(&mut self) -> Result<i16, WorldFileError> {
+        Ok(i16::from_le_bytes(self.take()?))
+    }
+
+    fn i32(&mut self) -> Result<i32, WorldFileError> {
+        Ok(i32::from_le_bytes(self.take()?))
+    }
+
+    /// Reads a .NET string, which is prefixed by its length as a 7-bit encoded integer
+    fn string(&mut self) -> Result<String, WorldFileError> {
+        let mut len = 0;
+        for shift in (0..35).step_by(7) {
+            let byte = self.u8()?;
+            len |= ((byte & 0x7F) as usize) << shift;
+            if byte & 0x80 == 0 { break }
+        }
+        let bytes = self.bytes.get(self.pos..self.pos + len).ok_or(WorldFileError::UnexpectedEof)?;
+        self.pos += len;
+        String::from_utf8(bytes.to_vec()).map_err(|_| WorldFileError::InvalidString)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{boss::Boss, loadout_data::Stage};
+
+    use super::{parse_modded_flags, WorldFile, WorldFileError};
+
+    #[test]
+    fn parses_pre_mech_world() {
+        let world = WorldFile::parse(include_bytes!("../tests/fixtures/premech.wld")).expect("world parses");
+
+        assert_eq!(world.name, "Pre Mech World");
+        assert_eq!((world.width, world.height), (6400, 1800));
+        assert!(world.crimson);
+        assert!(world.hard_mode);
+        assert_eq!(world.game_mode_name(), "Expert");
+        assert_eq!(world.defeated, vec![
+            Boss::EyeOfCthulhu,
+            Boss::EaterOfWorldsOrBrainOfCthulhu,
+            Boss::Skeletron,
+            Boss::KingSlime,
+            Boss::WallOfFlesh,
+        ]);
+        assert_eq!(Stage::PreBoss.furthest(&world.defeated, false), Stage::PreMech);
+    }
+
+    #[test]
+    fn parses_older_world_version() {
+        let world = WorldFile::parse(include_bytes!("../tests/fixtures/postmoonlord.wld")).expect("world parses");
+
+        assert_eq!(world.seed, "12345");
+        assert!(world.defeated.contains(&Boss::LunaticCultist));
+        assert!(world.defeated.contains(&Boss::MoonLord));
+        assert_eq!(Stage::PreBoss.furthest(&world.defeated, false), Stage::PreProvidence);
+    }
+
+    #[test]
+    fn parses_calamity_flags() {
+        let world = WorldFile::parse(include_bytes!("../tests/fixtures/postmoonlord.wld")).expect("world parses");
+        let modded = parse_modded_flags(include_bytes!("../tests/fixtures/postmoonlord.twld")).expect("modded flags parse");
+
+        assert!(modded.contains(&Boss::Providence));
+        assert!(!modded.contains(&Boss::Polterghast));
+
+        let defeated = [world.defeated, modded].concat();
+        assert_eq!(Stage::PreBoss.furthest(&defeated, true), Stage::PrePolterghast);
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        assert!(matches!(WorldFile::parse(b"not a world"), Err(WorldFileError::InvalidMagic)));
+        assert!(matches!(WorldFile::parse(&194_i32.to_le_bytes()), Err(WorldFileError::UnsupportedVersion(194))));
+        assert!(matches!(WorldFile::parse(&279_i32.to_le_bytes()), Err(WorldFileError::UnexpectedEof)));
+        assert!(parse_modded_flags(b"not a world").is_err());
+    }
+}
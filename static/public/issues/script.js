@@ -0,0 +1,137 @@
+Promise.all([fetchJson('/api/issues'), fetchJson('/api/changelog')])
+  .then(([issues, changelog]) => init(issues, changelog), handleError);
+
+/**
+  * @param {string} url
+  */
+function fetchJson(url) {
+  return fetch(url).then(response => new Promise((resolve, reject) => {
+    if (!response.ok) return response.text().then(reject, reject);
+    response.json().then(resolve, reject);
+  }));
+}
+
+function handleError(err) {
+  console.error(err);
+
+  const loadingText = document.getElementById('loading-text');
+  loadingText.innerText = err;
+  loadingText.classList.add('error');
+}
+
+/**
+  * @param {{
+      id: string,
+      status: string,
+      closed: boolean,
+      class: string,
+      stage: string,
+      stage_img: string,
+      section?: string,
+      incorrect: string,
+      correct: string,
+      explanation?: string,
+      supporters: number,
+      reporter?: { id: string, name: string },
+      created_at: number,
+      updated_at?: number,
+  * }[]} issues
+  * @param {{
+      id: number,
+      issue?: string,
+      title: string,
+      before: string,
+      after: string,
+      url: string,
+      changed_at: number,
+  * }[]} changelog
+  */
+function init(issues, changelog) {
+  document.getElementById('loading').style.display = 'none';
+  document.getElementById('body').style.display = null;
+
+  appendIssues(document.getElementById('open'), issues.filter(issue => !issue.closed), 'No open issues right now');
+  appendIssues(document.getElementById('resolved'), issues.filter(issue => issue.status == 'Resolved'), 'No issues have been resolved yet');
+
+  const list = document.getElementById('changelog');
+  if (changelog.length == 0) {
+    const empty = document.createElement('li');
+    empty.innerText = 'No loadout changes yet';
+    list.appendChild(empty);
+  }
+  for (const entry of changelog) {
+    const item = document.createElement('li');
+
+    const link = document.createElement('a');
+    link.href = entry.url;
+    link.innerText = entry.title;
+    item.appendChild(link);
+
+    item.appendChild(document.createTextNode(`: "${entry.before}" → "${entry.after}" (${new Date(entry.changed_at * 1000).toLocaleDateString()})`));
+    list.appendChild(item);
+  }
+}
+
+/**
+  * @param {Element} container
+  * @param {object[]} issues
+  * @param {string} emptyText
+  */
+function appendIssues(container, issues, emptyText) {
+  if (issues.length == 0) {
+    const empty = document.createElement('p');
+    empty.classList.add('empty');
+    empty.innerText = emptyText;
+    container.replaceWith(empty);
+    return;
+  }
+
+  for (const issue of issues) {
+    const item = document.createElement('div');
+    item.classList.add('issue');
+
+    const stageImg = document.createElement('img');
+    stageImg.classList.add('pixel');
+    stageImg.src = issue.stage_img;
+    stageImg.alt = 'stage icon';
+    item.appendChild(stageImg);
+
+    const info = document.createElement('div');
+    info.classList.add('info');
+
+    const title = document.createElement('h3');
+    title.innerText = `Issue ${issue.id}`;
+    const status = document.createElement('span');
+    status.classList.add('status');
+    status.innerText = issue.status;
+    title.appendChild(status);
+    info.appendChild(title);
+
+    appendDetail(info, 'Loadout', `${issue.class} - ${issue.stage}`);
+    if (issue.section) appendDetail(info, 'Section', issue.section);
+    appendDetail(info, 'Incorrect', issue.incorrect);
+    appendDetail(info, 'Correct', issue.correct);
+    if (issue.explanation) appendDetail(info, 'Explanation', issue.explanation);
+    if (issue.supporters > 0) appendDetail(info, 'Also reported by', `${issue.supporters} more`);
+    if (issue.reporter) appendDetail(info, 'Reported by', issue.reporter.name);
+    appendDetail(info, 'Reported', new Date(issue.created_at * 1000).toLocaleDateString());
+    if (issue.updated_at) appendDetail(info, 'Updated', new Date(issue.updated_at * 1000).toLocaleDateString());
+
+    item.appendChild(info);
+    container.appendChild(item);
+  }
+}
+
+/**
+  * @param {Element} parent
+  * @param {string} label
+  * @param {string} value
+  */
+function appendDetail(parent, label, value) {
+  const p = document.createElement('p');
+  const strong = document.createElement('strong');
+  strong.innerText = `${label}: `;
+  p.appendChild(strong);
+  p.appendChild(document.createTextNode(value));
+  parent.appendChild(p);
+}
@@ -0,0 +1,117 @@
+fetch('/api/lfg')
+  .then(response => new Promise((resolve, reject) => {
+    if (!response.ok) return response.text().then(reject, reject);
+    response.json().then(resolve, reject);
+  }), handleError)
+  .then(init, handleError);
+
+function handleError(err) {
+  console.error(err);
+
+  const loadingText = document.getElementById('loading-text');
+  loadingText.innerText = err;
+  loadingText.classList.add('error');
+}
+
+/**
+  * @param {{
+      owner: string,
+      owner_name: string,
+      description: string,
+      stage: string,
+      stage_img: string,
+      difficulty?: string,
+      players: string[],
+      any_class: boolean,
+      wanted_classes: string[],
+      listed_at: number,
+  * }[]} listings
+  */
+function init(listings) {
+  document.getElementById('loading').style.display = 'none';
+  document.getElementById('body').style.display = null;
+
+  const container = document.getElementById('listings');
+
+  if (listings.length == 0) {
+    const empty = document.createElement('h2');
+    empty.innerText = 'No public playthroughs right now';
+    container.appendChild(empty);
+    return;
+  }
+
+  for (const listing of listings) {
+    const item = document.createElement('div');
+    item.classList.add('listing');
+
+    const stageImg = document.createElement('img');
+    stageImg.classList.add('pixel');
+    stageImg.src = listing.stage_img;
+    stageImg.alt = 'stage icon';
+    item.appendChild(stageImg);
+
+    const info = document.createElement('div');
+    info.classList.add('info');
+
+    const title = document.createElement('h2');
+    title.innerText = `${listing.owner_name}'s Playthrough`;
+    info.appendChild(title);
+
+    const description = document.createElement('p');
+    description.innerText = listing.description;
+    info.appendChild(description);
+
+    appendDetail(info, 'Stage', listing.stage);
+    if (listing.difficulty) appendDetail(info, 'Difficulty', listing.difficulty);
+    appendDetail(info, 'Listed', new Date(listing.listed_at * 1000).toLocaleDateString());
+
+    appendClasses(info, 'Players', listing.players);
+    if (listing.any_class) {
+      appendDetail(info, 'Looking for', 'Any class');
+    } else if (listing.wanted_classes.length == 0) {
+      appendDetail(info, 'Looking for', 'No classes left');
+    } else {
+      appendClasses(info, 'Looking for', listing.wanted_classes);
+    }
+
+    item.appendChild(info);
+    container.appendChild(item);
+  }
+}
+
+/**
+  * @param {Element} parent
+  * @param {string} label
+  * @param {string} value
+  */
+function appendDetail(parent, label, value) {
+  const p = document.createElement('p');
+  const strong = document.createElement('strong');
+  strong.innerText = `${label}: `;
+  p.appendChild(strong);
+  p.appendChild(document.createTextNode(value));
+  parent.appendChild(p);
+}
+
+/**
+  * @param {Element} parent
+  * @param {string} label
+  * @param {string[]} classes
+  */
+function appendClasses(parent, label, classes) {
+  const p = document.createElement('p');
+  p.classList.add('classes');
+  const strong = document.createElement('strong');
+  strong.innerText = `${label}: `;
+  p.appendChild(strong);
+
+  for (const calamityClass of classes) {
+    const img = document.createElement('img');
+    img.classList.add('pixel');
+    img.src = `/assets/emoji/${calamityClass.toLowerCase()}.png`;
+    img.alt = calamityClass;
+    img.title = calamityClass;
+    p.appendChild(img);
+  }
+  parent.appendChild(p);
+}
@@ -0,0 +1,101 @@
+const searchParams = new URLSearchParams(window.location.search);
+
+const id = searchParams.get('id');
+
+if (id == null) window.location = '/';
+
+fetch(`/api/recap/${id}`)
+  .then(response => new Promise((resolve, reject) => {
+    if (!response.ok) return response.text().then(reject, reject);
+    response.json().then(resolve, reject);
+  }), handleError)
+  .then(init, handleError);
+
+function handleError(err) {
+  console.error(err);
+
+  const loadingText = document.getElementById('loading-text');
+  loadingText.innerText = err || 'Recap not found';
+  loadingText.classList.add('error');
+}
+
+/**
+  * @param {{
+      owner_name: string,
+      players: [string, string][],
+      final_stage: string,
+      final_stage_img: string,
+      started?: number,
+      ended: number,
+      active_seconds?: number,
+      stages: [string, number][],
+      fastest?: string,
+      slowest?: string,
+      defeated: string[],
+  * }} data
+  */
+function init(data) {
+  document.getElementById('loading').style.display = 'none';
+  document.getElementById('body').style.display = null;
+
+  document.getElementById('title').innerText = `${data.owner_name}'s Playthrough Recap`;
+  document.getElementById('heading').innerText = `${data.owner_name}'s Playthrough`;
+  document.getElementById('stage-img').src = data.final_stage_img;
+
+  const players = document.getElementById('players');
+  for (const [name, calamityClass] of data.players) {
+    const li = document.createElement('li');
+    const img = document.createElement('img');
+    img.classList.add('pixel');
+    img.src = `/assets/emoji/${calamityClass.toLowerCase()}.png`;
+    img.alt = calamityClass;
+    li.appendChild(img);
+    li.appendChild(document.createTextNode(`${name} - ${calamityClass}`));
+    players.appendChild(li);
+  }
+
+  document.getElementById('final-stage').innerText = data.final_stage;
+  document.getElementById('dates').innerText = data.started
+    ? `${formatDate(data.started)} - ${formatDate(data.ended)}`
+    : 'Never started';
+  document.getElementById('active-time').innerText = data.active_seconds != null ? formatDuration(data.active_seconds) : 'Never started';
+  document.getElementById('fastest').innerText = data.fastest ?? '-';
+  document.getElementById('slowest').innerText = data.slowest ?? '-';
+
+  const stages = document.getElementById('stages');
+  for (const [stage, seconds] of data.stages) {
+    const li = document.createElement('li');
+    li.innerText = `${stage}: ${formatDuration(seconds)}`;
+    stages.appendChild(li);
+  }
+
+  const defeated = document.getElementById('defeated');
+  for (const boss of data.defeated) {
+    const li = document.createElement('li');
+    li.innerText = boss;
+    defeated.appendChild(li);
+  }
+}
+
+/**
+  * @param {number} timestamp
+  */
+function formatDate(timestamp) {
+  return new Date(timestamp * 1000).toLocaleDateString();
+}
+
+/**
+  * @param {number} seconds
+  */
+function formatDuration(seconds) {
+  const units = [
+    [Math.floor(seconds / 86400), 'day'],
+    [Math.floor(seconds / 3600) % 24, 'hour'],
+    [Math.floor(seconds / 60) % 60, 'minute'],
+  ];
+  const parts = units
+    .filter(([amount]) => amount > 0)
+    .map(([amount, unit]) => `${amount} ${unit}${amount == 1 ? '' : 's'}`);
+
+  return parts.length > 0 ? parts.join(', ') : `${seconds} second${seconds == 1 ? '' : 's'}`;
+}
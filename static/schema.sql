@@ -58,12 +58,109 @@ CREATE TABLE IF NOT EXISTS playthroughs (
   started TIMESTAMP
 );
 
+ALTER TABLE playthroughs ADD COLUMN IF NOT EXISTS last_activity TIMESTAMP NOT NULL DEFAULT now();
+ALTER TABLE playthroughs ADD COLUMN IF NOT EXISTS stale_notified TIMESTAMP;
+ALTER TABLE playthroughs ADD COLUMN IF NOT EXISTS guild_id NUMERIC(20, 0);
+ALTER TABLE playthroughs ADD COLUMN IF NOT EXISTS channel_id NUMERIC(20, 0);
+ALTER TABLE playthroughs ADD COLUMN IF NOT EXISTS role_id NUMERIC(20, 0);
+ALTER TABLE playthroughs ADD COLUMN IF NOT EXISTS role_guild_id NUMERIC(20, 0);
+UPDATE playthroughs SET role_guild_id = guild_id WHERE role_id IS NOT NULL AND role_guild_id IS NULL;
+ALTER TABLE playthroughs ADD COLUMN IF NOT EXISTS dashboard_channel_id NUMERIC(20, 0);
+ALTER TABLE playthroughs ADD COLUMN IF NOT EXISTS dashboard_message_id NUMERIC(20, 0);
+
+CREATE TABLE IF NOT EXISTS archived_playthroughs (
+  id SERIAL PRIMARY KEY,
+  owner NUMERIC(20, 0) NOT NULL,
+  stage SMALLINT NOT NULL CHECK (stage BETWEEN 0 AND 14),
+  started TIMESTAMP,
+  archived_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
 CREATE TABLE IF NOT EXISTS playthrough_players (
   user_id NUMERIC(20, 0) PRIMARY KEY,
   playthrough_owner NUMERIC(20, 0) NOT NULL REFERENCES playthroughs(owner) ON DELETE CASCADE,
   class SMALLINT NOT NULL CHECK (class BETWEEN 0 AND 4)
 );
 
+ALTER TABLE playthrough_players ADD COLUMN IF NOT EXISTS stage SMALLINT CHECK (stage BETWEEN 0 AND 14);
+
+CREATE TABLE IF NOT EXISTS playthrough_bosses (
+  playthrough_owner NUMERIC(20, 0) NOT NULL REFERENCES playthroughs(owner) ON DELETE CASCADE,
+  boss SMALLINT NOT NULL CHECK (boss BETWEEN 0 AND 27),
+  defeated_at TIMESTAMP NOT NULL DEFAULT now(),
+  PRIMARY KEY (playthrough_owner, boss)
+);
+
+CREATE TABLE IF NOT EXISTS playthrough_pauses (
+  playthrough_owner NUMERIC(20, 0) NOT NULL REFERENCES playthroughs(owner) ON DELETE CASCADE,
+  paused TIMESTAMP NOT NULL,
+  resumed TIMESTAMP,
+  PRIMARY KEY (playthrough_owner, paused)
+);
+
+CREATE TABLE IF NOT EXISTS playthrough_reminders (
+  id SERIAL PRIMARY KEY,
+  playthrough_owner NUMERIC(20, 0) NOT NULL REFERENCES playthroughs(owner) ON DELETE CASCADE,
+  remind_at TIMESTAMP NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS playthrough_settings (
+  playthrough_owner NUMERIC(20, 0) PRIMARY KEY REFERENCES playthroughs(owner) ON DELETE CASCADE,
+  difficulty SMALLINT CHECK (difficulty BETWEEN 0 AND 6),
+  world_name VARCHAR(255),
+  world_size SMALLINT CHECK (world_size BETWEEN 0 AND 2),
+  seed VARCHAR(255),
+  evil SMALLINT CHECK (evil BETWEEN 0 AND 1),
+  mods TEXT[] NOT NULL DEFAULT '{}',
+  notes TEXT
+);
+
+ALTER TABLE playthrough_settings ADD COLUMN IF NOT EXISTS unique_classes BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE playthrough_settings ADD COLUMN IF NOT EXISTS vote_majority SMALLINT CHECK (vote_majority BETWEEN 1 AND 100);
+
+CREATE TABLE IF NOT EXISTS playthrough_listings (
+  playthrough_owner NUMERIC(20, 0) PRIMARY KEY REFERENCES playthroughs(owner) ON DELETE CASCADE,
+  owner_name VARCHAR(255) NOT NULL,
+  description VARCHAR(255) NOT NULL,
+  wanted_classes SMALLINT[] NOT NULL DEFAULT '{}',
+  listed_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE TABLE IF NOT EXISTS playthrough_stages (
+  id SERIAL PRIMARY KEY,
+  playthrough_owner NUMERIC(20, 0) NOT NULL REFERENCES playthroughs(owner) ON DELETE CASCADE,
+  stage SMALLINT NOT NULL CHECK (stage BETWEEN 0 AND 14),
+  reached_at TIMESTAMP NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS progress_votes (
+  playthrough_owner NUMERIC(20, 0) PRIMARY KEY REFERENCES playthroughs(owner) ON DELETE CASCADE,
+  stage SMALLINT NOT NULL CHECK (stage BETWEEN 0 AND 14),
+  started_by NUMERIC(20, 0) NOT NULL,
+  channel_id NUMERIC(20, 0) NOT NULL,
+  message_id NUMERIC(20, 0) NOT NULL,
+  expires_at TIMESTAMP NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS progress_vote_ballots (
+  playthrough_owner NUMERIC(20, 0) NOT NULL REFERENCES progress_votes(playthrough_owner) ON DELETE CASCADE,
+  user_id NUMERIC(20, 0) NOT NULL,
+  approve BOOLEAN NOT NULL,
+  PRIMARY KEY (playthrough_owner, user_id)
+);
+
+CREATE TABLE IF NOT EXISTS playthrough_recaps (
+  id SERIAL PRIMARY KEY,
+  owner NUMERIC(20, 0) NOT NULL,
+  data TEXT NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+ALTER TABLE playthrough_recaps ADD COLUMN IF NOT EXISTS token VARCHAR(16);
+UPDATE playthrough_recaps SET token = substr(md5(random()::text || id::text), 1, 16) WHERE token IS NULL;
+ALTER TABLE playthrough_recaps ALTER COLUMN token SET NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS playthrough_recaps_token ON playthrough_recaps(token);
+
 CREATE TABLE IF NOT EXISTS issues (
   id INT PRIMARY KEY,
   author NUMERIC(20, 0) NOT NULL,
@@ -74,3 +171,62 @@ CREATE TABLE IF NOT EXISTS issues (
   created_at TIMESTAMP DEFAULT now()
 );
 
+ALTER TABLE issues ADD COLUMN IF NOT EXISTS status SMALLINT NOT NULL DEFAULT 0 CHECK (status BETWEEN 0 AND 4);
+ALTER TABLE issues ADD COLUMN IF NOT EXISTS section VARCHAR(255);
+ALTER TABLE issues ADD COLUMN IF NOT EXISTS author_name VARCHAR(255);
+ALTER TABLE issues ADD COLUMN IF NOT EXISTS explanation VARCHAR(1000);
+ALTER TABLE issues ADD COLUMN IF NOT EXISTS attachments TEXT[] NOT NULL DEFAULT '{}';
+UPDATE issues SET attachments = ARRAY(SELECT regexp_replace(link, '^.*/([^/?]+)(\?.*)?$', '\1') FROM unnest(attachments) AS link)
+  WHERE array_to_string(attachments, ' ') LIKE '%://%';
+ALTER TABLE issues ALTER COLUMN class DROP NOT NULL;
+ALTER TABLE issues ADD COLUMN IF NOT EXISTS channel_id NUMERIC(20, 0);
+ALTER TABLE issues ADD COLUMN IF NOT EXISTS message_id NUMERIC(20, 0);
+
+CREATE TABLE IF NOT EXISTS issue_status_changes (
+  id SERIAL PRIMARY KEY,
+  issue_id INT NOT NULL REFERENCES issues(id) ON DELETE CASCADE,
+  status SMALLINT NOT NULL CHECK (status BETWEEN 0 AND 4),
+  changed_by NUMERIC(20, 0) NOT NULL,
+  reason VARCHAR(255),
+  changed_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE TABLE IF NOT EXISTS issue_supporters (
+  issue_id INT NOT NULL REFERENCES issues(id) ON DELETE CASCADE,
+  user_id NUMERIC(20, 0) NOT NULL,
+  added_at TIMESTAMP NOT NULL DEFAULT now(),
+  PRIMARY KEY (issue_id, user_id)
+);
+
+CREATE TABLE IF NOT EXISTS loadout_changelog (
+  id SERIAL PRIMARY KEY,
+  issue_id INT REFERENCES issues(id) ON DELETE SET NULL,
+  stage SMALLINT NOT NULL CHECK (stage BETWEEN 0 AND 14),
+  class SMALLINT CHECK (class BETWEEN 0 AND 4),
+  section VARCHAR(255) NOT NULL,
+  before TEXT NOT NULL,
+  after TEXT NOT NULL,
+  changed_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE TABLE IF NOT EXISTS issue_reviewer_roles (
+  guild_id NUMERIC(20, 0) PRIMARY KEY,
+  role_id NUMERIC(20, 0) NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS report_blocks (
+  user_id NUMERIC(20, 0) PRIMARY KEY,
+  blocked_by NUMERIC(20, 0) NOT NULL,
+  reason VARCHAR(255),
+  blocked_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE TABLE IF NOT EXISTS user_preferences (
+  user_id NUMERIC(20, 0) PRIMARY KEY,
+  delivery SMALLINT NOT NULL DEFAULT 0 CHECK (delivery BETWEEN 0 AND 2),
+  compact BOOLEAN NOT NULL DEFAULT false,
+  extra_classes SMALLINT[] NOT NULL DEFAULT '{}'
+);
+
+ALTER TABLE user_preferences ADD COLUMN IF NOT EXISTS mute_issue_updates BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE user_preferences ADD COLUMN IF NOT EXISTS public_reports BOOLEAN NOT NULL DEFAULT false;